@@ -0,0 +1,108 @@
+// Rough timings for interval arithmetic, run with `cargo bench`.
+//
+// Each benchmark runs a few times and reports the fastest run,
+// which is plenty to see whether something got faster or slower.
+use egg::*;
+use egg_tutorial_pldi_2022::*;
+use num::BigRational;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+fn saturate<B: Bound + 'static>(
+    expr: &str,
+    intervals: &[(&str, &str)],
+    node_limit: usize,
+) -> Interval<B> {
+    let expr: RecExpr<Math> = expr.parse().unwrap();
+    let mut runner = Runner::<Math, IntervalAnalysis<B>, ()>::default()
+        .with_node_limit(node_limit)
+        .with_time_limit(Duration::from_secs(60))
+        .with_expr(&expr);
+    let root = runner.roots[0];
+    for (var, interval) in intervals {
+        let id = runner.egraph.add(Math::Var(Symbol::from(*var)));
+        runner
+            .egraph
+            .set_analysis_data(id, Interval::ival(interval));
+    }
+    runner.egraph.rebuild();
+    runner = runner.run(&interval_rules());
+    runner.egraph[root].data.clone()
+}
+
+fn bench<T>(name: &str, runs: usize, mut f: impl FnMut() -> T) {
+    let fastest = (0..runs)
+        .map(|_| {
+            let start = Instant::now();
+            black_box(f());
+            start.elapsed()
+        })
+        .min()
+        .unwrap();
+    println!("{:<40} {:>12.3?}", name, fastest);
+}
+
+fn main() {
+    // the same arithmetic on plain big rationals, which allocate for every number,
+    // and on `Rational`, which stays in i64s while it can.
+    // The numerators are odd so nothing divides by zero.
+    let q = |n: i64, d: i64| BigRational::new(n.into(), d.into());
+    let bounds: Vec<_> = (1..200).map(|n| q(2 * (n % 9) - 9, n % 5 + 1)).collect();
+    let small: Vec<_> = bounds.iter().cloned().map(Rational::from).collect();
+    bench("bounds, BigRational", 20, || {
+        bounds
+            .iter()
+            .zip(bounds.iter().rev())
+            .map(|(a, b)| (a + b) * (a - b) / b)
+            .collect::<Vec<_>>()
+    });
+    bench("bounds, Rational", 20, || {
+        small
+            .iter()
+            .zip(small.iter().rev())
+            .map(|(a, b)| (a + b) * (a - b) / b)
+            .collect::<Vec<_>>()
+    });
+
+    let intervals: Vec<_> = (1..200)
+        .map(|n| Interval::new(Some(q(n % 7 - 3, 2)), Some(q(n % 7, 3))))
+        .collect();
+    let small: Vec<_> = (1..200)
+        .map(|n| {
+            Interval::new(
+                Some(Rational::new(n % 7 - 3, 2)),
+                Some(Rational::new(n % 7, 3)),
+            )
+        })
+        .collect();
+    bench("interval ops, BigRational", 20, || {
+        intervals
+            .iter()
+            .zip(intervals.iter().rev())
+            .map(|(a, b)| (a + b) * (a - b) / b)
+            .collect::<Vec<_>>()
+    });
+    bench("interval ops, Rational", 20, || {
+        small
+            .iter()
+            .zip(small.iter().rev())
+            .map(|(a, b)| (a + b) * (a - b) / b)
+            .collect::<Vec<_>>()
+    });
+
+    let paper = [("x", "0, 1"), ("y", "1, 2")];
+    let paper_example = "(- 1 (/ (* 2 y) (+ x y)))";
+    bench("saturation, paper example, BigRational", 5, || {
+        saturate::<BigRational>(paper_example, &paper, 10_000)
+    });
+    bench("saturation, paper example, Rational", 5, || {
+        saturate::<Rational>(paper_example, &paper, 10_000)
+    });
+    let larger = "(+ (/ (* 2 y) (+ x y)) (/ (- x (* 3 y)) (+ (* x x) (+ y 1))))";
+    bench("saturation, larger, BigRational", 3, || {
+        saturate::<BigRational>(larger, &paper, 50_000)
+    });
+    bench("saturation, larger, Rational", 3, || {
+        saturate::<Rational>(larger, &paper, 50_000)
+    });
+}
@@ -0,0 +1,225 @@
+use crate::Interval;
+use auto_ops::*;
+use num::{BigRational, One, Signed, Zero};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// An independent source of uncertainty, ranging over `[-1, 1]`.
+/// Two affine forms that share a noise symbol move together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Noise(usize);
+
+static NEXT_NOISE: AtomicUsize = AtomicUsize::new(0);
+
+impl Noise {
+    /// A noise symbol nothing else uses yet.
+    pub fn fresh() -> Self {
+        Noise(NEXT_NOISE.fetch_add(1, Ordering::Relaxed))
+    }
+}
+
+/// A number written as `c + a1*e1 + a2*e2 + ...`,
+/// where each `e` is a [`Noise`] symbol somewhere in `[-1, 1]`.
+///
+/// Unlike an [`Interval`], this remembers where the uncertainty came from,
+/// so `x - x` is exactly 0 and `x * (1 - x)` doesn't double count.
+/// It can't represent infinite ranges, open bounds or the empty set, though.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Affine {
+    center: BigRational,
+    // never holds a zero coefficient
+    terms: BTreeMap<Noise, BigRational>,
+}
+
+impl std::fmt::Display for Affine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.center)?;
+        for (Noise(n), coeff) in &self.terms {
+            let sign = if coeff.is_negative() { '-' } else { '+' };
+            write!(f, " {} {}*e{}", sign, coeff.abs(), n)?;
+        }
+        Ok(())
+    }
+}
+
+impl From<BigRational> for Affine {
+    fn from(center: BigRational) -> Self {
+        Self {
+            center,
+            terms: BTreeMap::new(),
+        }
+    }
+}
+
+impl From<&Affine> for Interval {
+    fn from(affine: &Affine) -> Self {
+        affine.to_interval()
+    }
+}
+
+impl Affine {
+    /// The form for a range, with a fresh noise symbol for its width.
+    /// Returns `None` for empty or unbounded intervals, and open bounds are treated as closed.
+    pub fn from_interval(interval: &Interval) -> Option<Self> {
+        let (center, radius) = (interval.midpoint()?, interval.radius()?);
+        Some(Self::from(BigRational::from(center)).with_noise(Noise::fresh(), radius.into()))
+    }
+
+    /// Adds `coeff * noise` to the form.
+    pub fn with_noise(mut self, noise: Noise, coeff: BigRational) -> Self {
+        let total = self.terms.remove(&noise).unwrap_or_else(Zero::zero) + coeff;
+        if !total.is_zero() {
+            self.terms.insert(noise, total);
+        }
+        self
+    }
+
+    pub fn center(&self) -> &BigRational {
+        &self.center
+    }
+
+    /// How far the form can get from its center.
+    pub fn radius(&self) -> BigRational {
+        self.terms.values().map(|c| c.abs()).sum()
+    }
+
+    /// Every value the form can take, forgetting the correlations.
+    pub fn to_interval(&self) -> Interval {
+        let radius = self.radius();
+        let (lo, hi) = (&self.center - &radius, &self.center + &radius);
+        Interval::new(Some(lo.into()), Some(hi.into()))
+    }
+
+    pub fn get_constant(&self) -> Option<&BigRational> {
+        self.terms.is_empty().then_some(&self.center)
+    }
+
+    fn scale(&self, k: &BigRational) -> Self {
+        self.terms
+            .iter()
+            .fold(Self::from(&self.center * k), |acc, (noise, coeff)| {
+                acc.with_noise(*noise, coeff * k)
+            })
+    }
+
+    /// `x * x`, which is tighter than `self * self` since both sides are the same `x`.
+    pub fn square(&self) -> Self {
+        // (c + r)^2 = c^2 + 2cr + r^2, where r^2 is somewhere in [0, radius^2]
+        let half_r2 = num::pow(self.radius(), 2) / BigRational::from_integer(2.into());
+        let linear = self.scale(&(&self.center * BigRational::from_integer(2.into())));
+        Self {
+            center: &self.center * &self.center + &half_r2,
+            terms: linear.terms,
+        }
+        .with_noise(Noise::fresh(), half_r2)
+    }
+
+    /// `1 / x`, or `None` if `x` might be zero.
+    pub fn recip(&self) -> Option<Self> {
+        if let Some(c) = self.get_constant() {
+            return (!c.is_zero()).then(|| Self::from(c.recip()));
+        }
+        let radius = self.radius();
+        let (lo, hi) = (&self.center - &radius, &self.center + &radius);
+        if !lo.is_positive() && !hi.is_negative() {
+            return None;
+        }
+        if hi.is_negative() {
+            return Some(-(-self).recip()?);
+        }
+        // The min-range approximation: 1/x is about -x/hi^2 plus a constant,
+        // and g(x) = 1/x + x/hi^2 falls from g(lo) to g(hi) in between.
+        let slope = -(&hi * &hi).recip();
+        let g = |x: &BigRational| x.recip() - x * &slope;
+        let (g_lo, g_hi) = (g(&lo), g(&hi));
+        let half = BigRational::new(1.into(), 2.into());
+        let offset = (&g_lo + &g_hi) * &half;
+        let error = (g_lo - g_hi) * half;
+        let scaled = self.scale(&slope);
+        Some(
+            Self {
+                center: scaled.center + offset,
+                ..scaled
+            }
+            .with_noise(Noise::fresh(), error),
+        )
+    }
+
+    /// `self / other`, or `None` if `other` might be zero.
+    pub fn checked_div(&self, other: &Self) -> Option<Self> {
+        Some(self * other.recip()?)
+    }
+}
+
+impl_op_ex!(-|a: &Affine| -> Affine { a.scale(&-BigRational::one()) });
+
+impl_op_ex!(+ |a: &Affine, b: &Affine| -> Affine {
+    let sum = Affine {
+        center: &a.center + &b.center,
+        terms: a.terms.clone(),
+    };
+    b.terms
+        .iter()
+        .fold(sum, |acc, (noise, coeff)| acc.with_noise(*noise, coeff.clone()))
+});
+
+impl_op_ex!(-|a: &Affine, b: &Affine| -> Affine { a + -b });
+
+impl_op_ex!(*|a: &Affine, b: &Affine| -> Affine {
+    // (a0 + ra)(b0 + rb) = a0 b0 + a0 rb + b0 ra + ra rb,
+    // and the last part is somewhere within radius(a) * radius(b)
+    let linear = a.scale(&b.center) + b.scale(&a.center);
+    let center = &a.center * &b.center;
+    let error = a.radius() * b.radius();
+    Affine { center, ..linear }.with_noise(Noise::fresh(), error)
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ival;
+
+    fn affine(s: &str) -> Affine {
+        Affine::from_interval(&ival(s)).unwrap()
+    }
+
+    #[test]
+    fn test_correlations() {
+        let x = affine("[0, 1]");
+        assert_eq!(x.to_interval(), ival("[0, 1]"));
+        assert_eq!((&x - &x).to_interval(), ival("[0, 0]"));
+        assert_eq!((&x + &x).to_interval(), ival("[0, 2]"));
+
+        // intervals would say [0, 1] here, the true range is [0, 1/4]
+        let one = Affine::from(BigRational::one());
+        assert_eq!((&x * (&one - &x)).to_interval(), ival("[0, 1/2]"));
+
+        // independent variables don't cancel
+        let y = affine("[0, 1]");
+        assert_eq!((&x - &y).to_interval(), ival("[-1, 1]"));
+
+        assert_eq!(affine("[-2, 2]").square().to_interval(), ival("[0, 4]"));
+        assert_eq!((&x * &x).to_interval(), ival("[-1/2, 1]"));
+        assert_eq!(
+            affine("[3, 3]").get_constant(),
+            Some(&BigRational::from_integer(3.into()))
+        );
+        assert_eq!(Affine::from_interval(&ival("[0, inf)")), None);
+    }
+
+    #[test]
+    fn test_division() {
+        let recip = |s: &str| affine(s).recip().map(|a| a.to_interval());
+        assert_eq!(recip("[1, 3]"), Some(ival("[1/3, 1]")));
+        assert_eq!(recip("[-3, -1]"), Some(ival("[-1, -1/3]")));
+        assert_eq!(recip("[2, 2]"), Some(ival("[1/2, 1/2]")));
+        assert_eq!(recip("[-1, 1]"), None);
+        assert_eq!(recip("[0, 1]"), None);
+
+        // x / x is still only approximately 1, but it's sound
+        let x = affine("[1, 2]");
+        let ratio = x.checked_div(&x).unwrap().to_interval();
+        assert!(ival("[1, 1]").is_subset_of(&ratio));
+        assert!(ratio.is_subset_of(&(ival("[1, 2]") / ival("[1, 2]"))));
+    }
+}
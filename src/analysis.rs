@@ -0,0 +1,276 @@
+use crate::{Affine, Bound, Interval, Math, Rational};
+use egg::{Analysis, DidMerge, EGraph, Id};
+use num::{BigRational, Zero};
+use std::marker::PhantomData;
+
+// The analysis itself (the ConstantFold struct) is a zero-sized type with
+// no data in it, it's just a marker.
+// It derives Default, since egg needs to be able to automatically
+// construct a (the only) value of this type.
+
+/// Tracks which e-classes are known to be a single constant, and adds that constant to them.
+///
+/// If two different constants end up in the same e-class, some rule must be unsound,
+/// so merging them panics with "bad merge!".
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ConstantFold;
+
+// Analysis is the egg trait for e-class analyses. It is parameterized over the
+// Language that the analysis is for.
+impl Analysis<Math> for ConstantFold {
+    // This associated type tells you what type is attached to
+    // each e-class. We'll use an optional number to indicate the
+    // constant (maybe) associated with each e-class.
+    type Data = Option<BigRational>;
+
+    // This function tells egg how to construct a `Data` for a particular e-node.
+    // It's typically where most of your logic in an e-class analysis goes.
+    fn make(egraph: &EGraph<Math, Self>, enode: &Math) -> Self::Data {
+        // first, we make a getter function that grabs the data for a given e-class id
+        let get = |id: &Id| egraph[*id].data.as_ref();
+
+        // now, we write the evaluator. Since the `Data` type is an `Option`, we
+        // can use the `?` operator in Rust, which tries to unpack the
+        // preceding optional value, "bailing" from the enclosing function if it's None
+        match enode {
+            Math::Num(n) => Some(n.clone()),
+            Math::Add([a, b]) => Some(get(a)? + get(b)?),
+            Math::Sub([a, b]) => Some(get(a)? - get(b)?),
+            Math::Mul([a, b]) => Some(get(a)? * get(b)?),
+            Math::Div([a, b]) => {
+                let b = get(b)?;
+                if !b.is_zero() {
+                    Some(get(a)? / b)
+                } else {
+                    None
+                }
+            }
+            Math::Var(_) => None,
+        }
+    }
+
+    // This function tells egg how to merge analysis data when
+    // e-classes get unioned. `to` is a mutable reference, which should
+    // be mutated as needed to be the Analysis data of the merged eclass.
+    // The return value is a `DidMerge`, which tells egg which "way" the merge went:
+    // whether `to` changed, and whether the result differs from `from`.
+    // It can be computed from the partial ordering over the `Data`.
+    fn merge(&mut self, to: &mut Self::Data, from: Self::Data) -> DidMerge {
+        match (to.as_mut(), from) {
+            // Neither side is known to be a constant so there's nothing
+            // to do when they merge.
+            (None, None) => DidMerge(false, false),
+
+            // Both sides are constants, so we should just make sure
+            // they're the same.
+            (Some(a), Some(b)) => {
+                assert_eq!(a, &b, "bad merge!");
+                DidMerge(false, false)
+            }
+
+            // The right side is a constant, so update `to` to be the same.
+            (None, Some(x)) => {
+                *to = Some(x);
+                DidMerge(true, false)
+            }
+
+            // The left side is a constant and the right is not, so `to`
+            // stays the same, but it now knows more than `from` did.
+            (Some(_), None) => DidMerge(false, true),
+        }
+        // Since Analysis data are so often Options, egg provides a
+        // combinator to make it easy to merge options:
+        // egg::merge_option(to, from, |a, b| {
+        //     assert_eq!(a, &b, "bad merge!");
+        //     DidMerge(false, false)
+        // })
+    }
+
+    // This is an optional function that allows you to modify the e-graph itself
+    // in response to changing e-class analysis values (or anything else).
+    // Here, we use it to actually insert new e-nodes corresponding to computed
+    // constant values.
+    fn modify(egraph: &mut EGraph<Math, Self>, id: Id) {
+        if let Some(n) = egraph[id].data.clone() {
+            let id2 = egraph.add(Math::Num(n));
+            egraph.union(id, id2);
+        }
+    }
+}
+
+/// Tracks an [`Interval`] for every e-class, and folds the ones that narrow down to a constant.
+///
+/// Variables start out as the whole number line;
+/// use [`EGraph::set_analysis_data`] to give them ranges.
+/// Merging intersects the ranges, and panics with "bad merge!" if they're disjoint,
+/// since that means the ranges given were impossible or a rule is unsound.
+///
+/// The bounds are exact [`Rational`]s unless you pick another [`Bound`], like `f64`.
+#[derive(Debug)]
+pub struct IntervalAnalysis<B = Rational> {
+    bits: Option<u32>,
+    // never holds a B, so copying or sending this needs nothing from B
+    phantom: PhantomData<fn() -> B>,
+}
+
+impl<B> Clone for IntervalAnalysis<B> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<B> Copy for IntervalAnalysis<B> {}
+
+impl<B> Default for IntervalAnalysis<B> {
+    fn default() -> Self {
+        Self {
+            bits: None,
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<B> IntervalAnalysis<B> {
+    /// Simplifies bounds to about `bits` bits after every operation,
+    /// so long saturation runs don't grind to a halt on huge denominators.
+    /// See [`Interval::simplify`].
+    pub fn with_bits(self, bits: u32) -> Self {
+        Self {
+            bits: Some(bits),
+            ..self
+        }
+    }
+}
+
+// The interval for one node given the intervals for its children,
+// shared with `interval_eval` so the two always agree.
+// Variables could be anything as far as this knows.
+pub(crate) fn apply_interval<'a, B: Bound + 'a>(
+    enode: &Math,
+    get: impl Fn(&Id) -> &'a Interval<B>,
+) -> Interval<B> {
+    match enode {
+        // constants like 1/3 might need rounding, so this isn't just a singleton
+        Math::Num(n) => Interval::from_rational(n),
+        Math::Add([a, b]) => get(a) + get(b),
+        Math::Sub([a, b]) => get(a) - get(b),
+        Math::Mul([a, b]) => get(a) * get(b),
+        Math::Div([a, b]) => get(a) / get(b),
+        Math::Var(_) => Interval::default(),
+    }
+}
+
+impl<B: Bound> Analysis<Math> for IntervalAnalysis<B> {
+    // We don't need an option for the e-class data type, since
+    // there is a reasonable default value (-inf, inf)
+    type Data = Interval<B>;
+
+    // The make is very similar to ConstantFold::make.
+    // We don't need to use `?`, since we can just use the default interval.
+    fn make(egraph: &EGraph<Math, Self>, enode: &Math) -> Self::Data {
+        let interval = apply_interval(enode, |id| &egraph[*id].data);
+        match egraph.analysis.bits {
+            Some(bits) => interval.simplify(bits),
+            None => interval,
+        }
+    }
+
+    // The merge function is more complicated than ConstantFold::merge.
+    // We want to do interval intersection.
+    // One way (commented out) is to just do the intersection and return a
+    // conservative approximation of the DidMerge.
+    // Instead, we compare the intersection against both sides
+    // in a way that returns precise merge information
+    fn merge(&mut self, to: &mut Self::Data, from: Self::Data) -> DidMerge {
+        // // a conservative manual implementation
+        // if to == &from {
+        //     DidMerge(false, false)
+        // } else {
+        //     *to = to.intersect(&from);
+        //     DidMerge(true, true)
+        // }
+
+        // If the intersection is empty, we just proved two things equal that
+        // can't possibly have the same value. Either a rule is unsound or
+        // the intervals we started with are infeasible, so we crash
+        // just like ConstantFold does.
+        let merged = to.intersect(&from);
+        assert!(
+            !merged.is_empty(),
+            "bad merge! {} and {} are disjoint",
+            to,
+            from
+        );
+        let did_merge = DidMerge(&merged != to, merged != from);
+        *to = merged;
+        did_merge
+    }
+
+    fn modify(egraph: &mut EGraph<Math, Self>, id: Id) {
+        // If the interval only includes one number, we can do constant folding
+        if let Some(constant) = egraph[id].data.get_constant() {
+            let new_id = egraph.add(Math::Num(constant.to_rational()));
+            egraph.union(id, new_id);
+        }
+    }
+}
+
+/// Tracks an [`Affine`] form for every e-class, which remembers how it depends on the variables,
+/// so `x * (1 - x)` on `[0, 1]` comes out as `[0, 1/2]` before any rewriting.
+/// Compare with [`IntervalAnalysis`] to see how much equality saturation adds on top.
+///
+/// Affine forms can't be unbounded, so `None` stands in for "anything",
+/// which is where variables start out. Give them ranges with
+/// [`EGraph::set_analysis_data`] and [`Affine::from_interval`],
+/// which gives each one its own noise symbol.
+///
+/// There's no intersection of affine forms, so merging keeps whichever form
+/// has the smaller radius. That's sound, but it's looser than intersecting intervals.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AffineAnalysis;
+
+impl Analysis<Math> for AffineAnalysis {
+    type Data = Option<Affine>;
+
+    fn make(egraph: &EGraph<Math, Self>, enode: &Math) -> Self::Data {
+        let get = |id: &Id| egraph[*id].data.as_ref();
+        match enode {
+            Math::Num(n) => Some(Affine::from(n.clone())),
+            Math::Add([a, b]) => Some(get(a)? + get(b)?),
+            Math::Sub([a, b]) => Some(get(a)? - get(b)?),
+            Math::Mul([a, b]) => Some(get(a)? * get(b)?),
+            Math::Div([a, b]) => get(a)?.checked_div(get(b)?),
+            Math::Var(_) => None,
+        }
+    }
+
+    // Both sides are sound, so just keep whichever gives the narrower range.
+    fn merge(&mut self, to: &mut Self::Data, from: Self::Data) -> DidMerge {
+        let radius = |a: &Self::Data| a.as_ref().map(Affine::radius);
+        match (radius(to), radius(&from)) {
+            (Some(r_to), Some(r_from)) if r_from < r_to => {
+                *to = from;
+                DidMerge(true, false)
+            }
+            // equal radii can still be different forms
+            (Some(_), Some(_)) => DidMerge(false, *to != from),
+            (None, Some(_)) => {
+                *to = from;
+                DidMerge(true, false)
+            }
+            (Some(_), None) => DidMerge(false, true),
+            (None, None) => DidMerge(false, false),
+        }
+    }
+
+    fn modify(egraph: &mut EGraph<Math, Self>, id: Id) {
+        let constant = egraph[id]
+            .data
+            .as_ref()
+            .and_then(|a| a.get_constant().cloned());
+        if let Some(constant) = constant {
+            let new_id = egraph.add(Math::Num(constant));
+            egraph.union(id, new_id);
+        }
+    }
+}
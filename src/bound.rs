@@ -0,0 +1,245 @@
+use num::{BigInt, BigRational, One, Signed, ToPrimitive, Zero};
+use std::fmt::{Debug, Display};
+
+/// Which way to round a result that can't be represented exactly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Round {
+    Down,
+    Up,
+}
+
+impl Round {
+    pub fn flip(self) -> Self {
+        match self {
+            Round::Down => Round::Up,
+            Round::Up => Round::Down,
+        }
+    }
+}
+
+/// The finite numbers that can bound an [`Interval`](crate::Interval).
+///
+/// Arithmetic takes a direction to round in.
+/// Exact types like [`BigRational`] can ignore it, but inexact ones like `f64`
+/// have to round that way, so lower bounds only go down and upper bounds only go up.
+/// That way every interval still contains the true result.
+/// Zero, one, signs and negation come from [`Signed`], since those are always exact.
+pub trait Bound: Signed + Clone + PartialOrd + Debug + Display {
+    fn add_rounded(&self, other: &Self, round: Round) -> Self;
+    fn sub_rounded(&self, other: &Self, round: Round) -> Self;
+    fn mul_rounded(&self, other: &Self, round: Round) -> Self;
+    fn div_rounded(&self, other: &Self, round: Round) -> Self;
+
+    /// The nearest bound in the given direction from a rational.
+    fn from_rational(n: &BigRational, round: Round) -> Self;
+
+    /// The exact value of the bound.
+    fn to_rational(&self) -> BigRational;
+
+    /// A nearby bound that is cheaper to compute with, in the given direction.
+    /// Bounds that are already small enough, or that don't get more expensive
+    /// as they grow, like `f64`, are returned as is.
+    fn simplify(&self, _bits: u32, _round: Round) -> Self {
+        self.clone()
+    }
+
+    /// Results that overflow to infinity aren't finite,
+    /// and intervals treat them as missing bounds.
+    fn is_finite(&self) -> bool {
+        true
+    }
+}
+
+/// Exact, but the numerators and denominators can get big.
+/// See [`Rational`](crate::Rational) for one that stays in machine integers while it can.
+impl Bound for BigRational {
+    fn add_rounded(&self, other: &Self, _round: Round) -> Self {
+        self + other
+    }
+
+    fn sub_rounded(&self, other: &Self, _round: Round) -> Self {
+        self - other
+    }
+
+    fn mul_rounded(&self, other: &Self, _round: Round) -> Self {
+        self * other
+    }
+
+    fn div_rounded(&self, other: &Self, _round: Round) -> Self {
+        self / other
+    }
+
+    fn from_rational(n: &BigRational, _round: Round) -> Self {
+        n.clone()
+    }
+
+    /// Rounds to about `bits` significant bits if the numerator or denominator is any bigger.
+    fn simplify(&self, bits: u32, round: Round) -> Self {
+        if self.numer().bits() <= bits as u64 && self.denom().bits() <= bits as u64 {
+            self.clone()
+        } else {
+            round_to_bits(self, bits, round)
+        }
+    }
+
+    fn to_rational(&self) -> BigRational {
+        self.clone()
+    }
+}
+
+// Rounds `x` down (or up) to a rational with about `bits` significant bits.
+pub(crate) fn round_to_bits(x: &BigRational, bits: u32, round: Round) -> BigRational {
+    if x.is_zero() {
+        return x.clone();
+    }
+    // |x| is within a factor of 2 of 2^exponent
+    let exponent = x.numer().bits() as i64 - x.denom().bits() as i64;
+    let shift = bits as i64 - exponent;
+    let scale = BigRational::from_integer(BigInt::one() << shift.unsigned_abs());
+    let scale = if shift >= 0 { scale } else { scale.recip() };
+    let scaled = x * &scale;
+    let rounded = match round {
+        Round::Down => scaled.floor(),
+        Round::Up => scaled.ceil(),
+    };
+    rounded / scale
+}
+
+// Below this, products and quotients may lose bits to underflow,
+// so the error-free tricks below stop being exact.
+const TINY: f64 = f64::MIN_POSITIVE * (1u64 << 53) as f64;
+
+// Nudges the round-to-nearest result `x` in the direction of `round`,
+// given the sign of the rounding error (true value minus `x`).
+fn nudge(x: f64, error: f64, round: Round) -> f64 {
+    match round {
+        Round::Down if x == f64::INFINITY => f64::MAX,
+        Round::Up if x == f64::NEG_INFINITY => f64::MIN,
+        Round::Down if error < 0.0 => x.next_down(),
+        Round::Up if error > 0.0 => x.next_up(),
+        _ => x,
+    }
+}
+
+// When the error can't be recovered, step outward unconditionally.
+fn step(x: f64, round: Round) -> f64 {
+    nudge(x, if round == Round::Down { -1.0 } else { 1.0 }, round)
+}
+
+/// Fast, with the usual round-to-nearest results nudged outward by one ulp
+/// whenever they weren't exact.
+impl Bound for f64 {
+    fn add_rounded(&self, other: &Self, round: Round) -> Self {
+        let sum = self + other;
+        if !sum.is_finite() {
+            return nudge(sum, 0.0, round);
+        }
+        // Knuth's TwoSum recovers the rounding error exactly
+        let a = sum - other;
+        let b = sum - a;
+        let error = (self - a) + (other - b);
+        nudge(sum, error, round)
+    }
+
+    fn sub_rounded(&self, other: &Self, round: Round) -> Self {
+        self.add_rounded(&-other, round)
+    }
+
+    fn mul_rounded(&self, other: &Self, round: Round) -> Self {
+        let product = self * other;
+        if !product.is_finite() {
+            return nudge(product, 0.0, round);
+        }
+        if product.abs() < TINY && *self != 0.0 && *other != 0.0 {
+            return step(product, round);
+        }
+        // a fused multiply-add computes the rounding error exactly
+        let error = self.mul_add(*other, -product);
+        nudge(product, error, round)
+    }
+
+    fn div_rounded(&self, other: &Self, round: Round) -> Self {
+        let quotient = self / other;
+        if !quotient.is_finite() {
+            return nudge(quotient, 0.0, round);
+        }
+        if quotient.abs() < TINY && *self != 0.0 {
+            return step(quotient, round);
+        }
+        // self - quotient * other is exact, and has the sign of
+        // the error once it's divided by `other`
+        let remainder = (-quotient).mul_add(*other, *self);
+        nudge(quotient, remainder * other.signum(), round)
+    }
+
+    fn from_rational(n: &BigRational, round: Round) -> Self {
+        let mut x = n.to_f64().unwrap_or(f64::NAN);
+        if x.is_nan() {
+            x = if n.is_negative() {
+                f64::NEG_INFINITY
+            } else {
+                f64::INFINITY
+            };
+        }
+        if !x.is_finite() {
+            return nudge(x, 0.0, round);
+        }
+        // to_f64 rounds to nearest, so this steps at most once
+        loop {
+            let exact = BigRational::from_float(x).unwrap();
+            match round {
+                Round::Down if &exact > n => x = x.next_down(),
+                Round::Up if &exact < n => x = x.next_up(),
+                _ => return x,
+            }
+        }
+    }
+
+    fn to_rational(&self) -> BigRational {
+        BigRational::from_float(*self).expect("bounds are finite")
+    }
+
+    fn is_finite(&self) -> bool {
+        f64::is_finite(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(exact: BigRational, f: impl Fn(Round) -> f64) {
+        let (lo, hi) = (f(Round::Down), f(Round::Up));
+        assert!(lo.to_rational() <= exact && exact <= hi.to_rational());
+        assert!(
+            hi == lo || hi == lo.next_up(),
+            "{} and {} too far apart",
+            lo,
+            hi
+        );
+    }
+
+    #[test]
+    fn test_f64_rounding() {
+        let r = |x: f64| x.to_rational();
+        let (a, b) = (0.1, 0.2);
+        check(r(a) + r(b), |round| a.add_rounded(&b, round));
+        check(r(a) - r(b), |round| a.sub_rounded(&b, round));
+        check(r(a) * r(b), |round| a.mul_rounded(&b, round));
+        check(r(a) / r(b), |round| a.div_rounded(&b, round));
+        check(r(1.0) / r(3.0), |round| 1.0.div_rounded(&3.0, round));
+        check(r(-1.0) / r(3.0), |round| (-1.0).div_rounded(&3.0, round));
+
+        // exact results aren't nudged
+        assert_eq!(0.5.add_rounded(&0.25, Round::Up), 0.75);
+        assert_eq!(3.0.mul_rounded(&0.5, Round::Down), 1.5);
+
+        // overflow only goes to infinity in the direction of rounding
+        assert_eq!(f64::MAX.add_rounded(&f64::MAX, Round::Down), f64::MAX);
+        assert_eq!(f64::MAX.add_rounded(&f64::MAX, Round::Up), f64::INFINITY);
+
+        let third = BigRational::new(1.into(), 3.into());
+        check(third.clone(), |round| f64::from_rational(&third, round));
+        assert_eq!(f64::from_rational(&r(0.5), Round::Up), 0.5);
+    }
+}
@@ -0,0 +1,200 @@
+//! Printing intervals as decimals instead of fractions.
+//!
+//! A bound like `1/3` can't be written out exactly in decimal,
+//! so it gets rounded outward: lower bounds down and upper bounds up.
+//! The printed interval always contains the real one.
+
+use crate::interval::parse_rational;
+use crate::{Bound, Interval, Round};
+use num::{BigInt, BigRational, Signed, Zero};
+use std::fmt;
+
+/// How to write out the bounds of an interval.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Notation {
+    /// The bounds exactly as they are, like `1/3`, so they parse back to the same interval.
+    /// A float that its usual decimal form doesn't capture, like the one nearest `0.1`,
+    /// is written as a fraction instead.
+    Exact,
+    /// Plain decimals with this many significant digits, like `0.3333`.
+    Decimal(usize),
+    /// Scientific notation with this many significant digits, like `3.333e-1`.
+    Scientific(usize),
+}
+
+/// Displays an interval in a given [`Notation`]. Made by [`Interval::display`].
+#[derive(Debug, Clone, Copy)]
+pub struct IntervalDisplay<'a, B> {
+    interval: &'a Interval<B>,
+    notation: Notation,
+}
+
+impl<'a, B> IntervalDisplay<'a, B> {
+    pub fn notation(self, notation: Notation) -> Self {
+        Self { notation, ..self }
+    }
+
+    pub fn decimal(self, digits: usize) -> Self {
+        self.notation(Notation::Decimal(digits))
+    }
+
+    pub fn scientific(self, digits: usize) -> Self {
+        self.notation(Notation::Scientific(digits))
+    }
+}
+
+impl<B: Bound> Interval<B> {
+    /// Displays the interval with a choice of notation, e.g.
+    /// `interval.display().scientific(3)`.
+    /// Formatting with a precision, like `{:.4}`, is the same as `.decimal(4)`
+    /// unless another notation was picked.
+    pub fn display(&self) -> IntervalDisplay<'_, B> {
+        IntervalDisplay {
+            interval: self,
+            notation: Notation::Exact,
+        }
+    }
+}
+
+fn pow10(n: i64) -> BigRational {
+    let p = BigRational::from_integer(BigInt::from(10).pow(n.unsigned_abs() as u32));
+    if n < 0 {
+        p.recip()
+    } else {
+        p
+    }
+}
+
+// Writes `x` with `digits` significant digits, rounded in the direction of `round`.
+fn write_rounded(
+    f: &mut fmt::Formatter<'_>,
+    x: &BigRational,
+    digits: usize,
+    round: Round,
+    scientific: bool,
+) -> fmt::Result {
+    if x.is_zero() {
+        return write!(f, "{}", if scientific { "0e0" } else { "0" });
+    }
+    let digits = digits.max(1) as i64;
+    // find the exponent with 10^exponent <= |x| < 10^(exponent + 1),
+    // starting from a guess based on the number of bits
+    let bits = x.numer().bits() as i64 - x.denom().bits() as i64;
+    let mut exponent = (bits as f64 * std::f64::consts::LOG10_2).floor() as i64;
+    while pow10(exponent) > x.abs() {
+        exponent -= 1;
+    }
+    while pow10(exponent + 1) <= x.abs() {
+        exponent += 1;
+    }
+
+    let scaled = x / pow10(exponent - digits + 1);
+    let mut mantissa = match round {
+        Round::Down => scaled.floor(),
+        Round::Up => scaled.ceil(),
+    }
+    .to_integer();
+    // rounding up can carry into an extra digit, like 9.99 to 10.0
+    if mantissa.abs() == BigInt::from(10).pow(digits as u32) {
+        mantissa /= 10;
+        exponent += 1;
+    }
+
+    let sign = if mantissa.is_negative() { "-" } else { "" };
+    let mantissa = mantissa.abs().to_string();
+    if scientific {
+        let (int, frac) = mantissa.split_at(1);
+        let frac = frac.trim_end_matches('0');
+        let point = if frac.is_empty() { "" } else { "." };
+        return write!(f, "{}{}{}{}e{}", sign, int, point, frac, exponent);
+    }
+
+    // the last digit of the mantissa is worth 10^shift
+    let shift = exponent - digits + 1;
+    if shift >= 0 {
+        return write!(f, "{}{}{}", sign, mantissa, "0".repeat(shift as usize));
+    }
+    let padded = format!("{:0>width$}", mantissa, width = (1 - shift) as usize);
+    let (int, frac) = padded.split_at(padded.len() - shift.unsigned_abs() as usize);
+    let frac = frac.trim_end_matches('0');
+    let point = if frac.is_empty() { "" } else { "." };
+    write!(f, "{}{}{}{}", sign, int, point, frac)
+}
+
+fn write_bound<B: Bound>(
+    f: &mut fmt::Formatter<'_>,
+    bound: &B,
+    notation: Notation,
+    round: Round,
+) -> fmt::Result {
+    match notation {
+        Notation::Exact => {
+            let exact = bound.to_rational();
+            if parse_rational(&bound.to_string()).as_ref() == Some(&exact) {
+                fmt::Display::fmt(bound, f)
+            } else {
+                fmt::Display::fmt(&exact, f)
+            }
+        }
+        Notation::Decimal(digits) => write_rounded(f, &bound.to_rational(), digits, round, false),
+        Notation::Scientific(digits) => write_rounded(f, &bound.to_rational(), digits, round, true),
+    }
+}
+
+impl<B: Bound> fmt::Display for IntervalDisplay<'_, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let interval = self.interval;
+        let notation = match (self.notation, f.precision()) {
+            (Notation::Exact, Some(digits)) => Notation::Decimal(digits),
+            (notation, _) => notation,
+        };
+        if interval.is_empty() {
+            return write!(f, "empty");
+        }
+        // infinite bounds are never closed
+        let closed_lo = interval.lo().is_some() && !interval.lo_open();
+        let closed_hi = interval.hi().is_some() && !interval.hi_open();
+        write!(f, "{}", if closed_lo { "[" } else { "(" })?;
+        match interval.lo() {
+            Some(lo) => write_bound(f, lo, notation, Round::Down)?,
+            None => write!(f, "-inf")?,
+        }
+        write!(f, ", ")?;
+        match interval.hi() {
+            Some(hi) => write_bound(f, hi, notation, Round::Up)?,
+            None => write!(f, "inf")?,
+        }
+        write!(f, "{}", if closed_hi { "]" } else { ")" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ival, Interval};
+
+    #[test]
+    fn test_decimal() {
+        let i = ival("[-3, 1/3)");
+        assert_eq!(format!("{}", i), "[-3, 1/3)");
+        assert_eq!(format!("{:.4}", i), "[-3, 0.3334)");
+        assert_eq!(i.display().decimal(2).to_string(), "[-3, 0.34)");
+        assert_eq!(format!("{:.3}", ival("[-2/3, 2/3]")), "[-0.667, 0.667]");
+        assert_eq!(format!("{:.2}", ival("(-inf, 12345]")), "(-inf, 13000]");
+        assert_eq!(format!("{:.2}", ival("[1/1000, 999/1000]")), "[0.001, 1]");
+        assert_eq!(format!("{:.4}", <Interval>::empty()), "empty");
+
+        // 0.1 isn't a float, so it was already rounded outward once
+        let tenth = Interval::<f64>::ival("0.1, 0.1");
+        assert_eq!(format!("{:.3}", tenth), "[0.0999, 0.101]");
+    }
+
+    #[test]
+    fn test_scientific() {
+        let sci = |s: &str, digits| ival(s).display().scientific(digits).to_string();
+        assert_eq!(sci("[-1/3, 1/3]", 3), "[-3.34e-1, 3.34e-1]");
+        assert_eq!(sci("[0, 123456789]", 2), "[0e0, 1.3e8]");
+        assert_eq!(sci("(1/7000, 1]", 2), "(1.4e-4, 1e0]");
+        assert_eq!(sci("[99999, inf)", 3), "[9.99e4, inf)");
+        assert_eq!(sci("[-99999, -99999]", 3), "[-1e5, -9.99e4]");
+    }
+}
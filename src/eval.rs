@@ -0,0 +1,144 @@
+use crate::analysis::apply_interval;
+use crate::{Bound, Interval, Math};
+use egg::{Id, Language, RecExpr, Symbol};
+use num::{BigRational, Zero};
+use std::collections::HashMap;
+
+/// Why a [`Math`] expression has no value.
+/// Each case holds the subterm responsible.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EvalError {
+    /// This division had a divisor that came out to zero.
+    DivisionByZero(RecExpr<Math>),
+    /// This variable wasn't given a value.
+    UnboundVariable(Symbol),
+}
+
+impl std::fmt::Display for EvalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DivisionByZero(term) => write!(f, "division by zero in {}", term),
+            Self::UnboundVariable(var) => write!(f, "variable {} has no value", var),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+/// Evaluates `expr` exactly, with variables looked up in `env`.
+///
+/// This is the plain meaning of a [`Math`] expression,
+/// so it's handy for checking that rewrites and optimized expressions didn't change anything.
+pub fn eval(
+    expr: &RecExpr<Math>,
+    env: &HashMap<Symbol, BigRational>,
+) -> Result<BigRational, EvalError> {
+    let nodes = expr.as_ref();
+    let mut values: Vec<BigRational> = Vec::with_capacity(nodes.len());
+    for node in nodes {
+        let get = |id: &Id| &values[usize::from(*id)];
+        let value = match node {
+            Math::Num(n) => n.clone(),
+            Math::Add([a, b]) => get(a) + get(b),
+            Math::Sub([a, b]) => get(a) - get(b),
+            Math::Mul([a, b]) => get(a) * get(b),
+            Math::Div([a, b]) if get(b).is_zero() => {
+                let term = node.build_recexpr(|id| nodes[usize::from(id)].clone());
+                return Err(EvalError::DivisionByZero(term));
+            }
+            Math::Div([a, b]) => get(a) / get(b),
+            Math::Var(v) => env.get(v).cloned().ok_or(EvalError::UnboundVariable(*v))?,
+        };
+        values.push(value);
+    }
+    Ok(values.pop().expect("expressions aren't empty"))
+}
+
+/// The interval `expr` falls in when each variable is somewhere in its interval in `env`,
+/// computed directly on the expression tree without an e-graph.
+/// Variables missing from `env` could be anything.
+///
+/// Each operator does the same thing it does in [`IntervalAnalysis`](crate::IntervalAnalysis),
+/// so this is the range the analysis starts from before any rewrites,
+/// and it's what equality saturation has to beat.
+pub fn interval_eval<B: Bound>(
+    expr: &RecExpr<Math>,
+    env: &HashMap<Symbol, Interval<B>>,
+) -> Interval<B> {
+    let mut values: Vec<Interval<B>> = Vec::with_capacity(expr.as_ref().len());
+    for node in expr.as_ref() {
+        let value = match node {
+            Math::Var(v) => env.get(v).cloned().unwrap_or_default(),
+            _ => apply_interval(node, |id| &values[usize::from(*id)]),
+        };
+        values.push(value);
+    }
+    values.pop().expect("expressions aren't empty")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ival;
+
+    fn env(vars: &[(&str, i64)]) -> HashMap<Symbol, BigRational> {
+        vars.iter()
+            .map(|(v, n)| (Symbol::from(*v), BigRational::from_integer((*n).into())))
+            .collect()
+    }
+
+    #[test]
+    fn test_eval() {
+        let run = |s: &str, vars: &[(&str, i64)]| eval(&s.parse().unwrap(), &env(vars));
+        let paper = "(- 1 (/ (* 2 y) (+ x y)))";
+        assert_eq!(
+            run(paper, &[("x", 1), ("y", 2)]),
+            Ok(BigRational::new((-1).into(), 3.into()))
+        );
+        assert_eq!(
+            run("(/ 1/2 x)", &[("x", 4)]),
+            Ok(BigRational::new(1.into(), 8.into()))
+        );
+
+        // the error points at the division, not the whole expression
+        match run(paper, &[("x", -2), ("y", 2)]) {
+            Err(EvalError::DivisionByZero(term)) => {
+                assert_eq!(term.to_string(), "(/ (* 2 y) (+ x y))")
+            }
+            other => panic!("expected division by zero, got {:?}", other),
+        }
+        assert_eq!(
+            run(paper, &[("x", 1)]),
+            Err(EvalError::UnboundVariable("y".into()))
+        );
+        assert_eq!(
+            run("(+ 1 (/ x 0))", &[("x", 1)]).unwrap_err().to_string(),
+            "division by zero in (/ x 0)"
+        );
+    }
+
+    #[test]
+    fn test_interval_eval() {
+        let paper: RecExpr<Math> = "(- 1 (/ (* 2 y) (+ x y)))".parse().unwrap();
+        let ranges = HashMap::from([("x".into(), ival("0, 1")), ("y".into(), ival("1, 2"))]);
+        assert_eq!(interval_eval(&paper, &ranges), ival("-3, 1/3"));
+
+        // the same expression at a single point agrees with plain evaluation
+        let point = |n: i64| Interval::singleton(BigRational::from_integer(n.into()));
+        let points = HashMap::from([("x".into(), point(1)), ("y".into(), point(2))]);
+        let exact = eval(&paper, &env(&[("x", 1), ("y", 2)]));
+        assert_eq!(
+            interval_eval(&paper, &points).get_constant(),
+            exact.ok().as_ref()
+        );
+
+        // missing variables are unbounded, and so is dividing by something that might be zero
+        assert_eq!(
+            interval_eval::<f64>(&paper, &HashMap::new()),
+            Interval::default()
+        );
+        let recip: RecExpr<Math> = "(/ 1 x)".parse().unwrap();
+        let ranges = HashMap::from([("x".into(), ival("-1, 1"))]);
+        assert_eq!(interval_eval(&recip, &ranges), Interval::default());
+    }
+}
@@ -0,0 +1,309 @@
+use crate::interval::parse_rational;
+use crate::Math;
+use egg::{Id, RecExpr, Symbol};
+use num::BigRational;
+
+/// Why a string couldn't be parsed as an infix [`Math`] expression.
+/// Columns count characters, starting from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseInfixError {
+    /// A character that isn't part of any token.
+    UnexpectedChar { column: usize, found: char },
+    /// Something other than what the grammar needed here, or the end of the input.
+    Expected {
+        column: usize,
+        expected: &'static str,
+        found: String,
+    },
+    /// The parenthesis at `column` was never closed.
+    Unclosed { column: usize },
+}
+
+impl ParseInfixError {
+    /// Where in the input things went wrong.
+    pub fn column(&self) -> usize {
+        match self {
+            Self::UnexpectedChar { column, .. }
+            | Self::Expected { column, .. }
+            | Self::Unclosed { column } => *column,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseInfixError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedChar { column, found } => {
+                write!(f, "unexpected {:?} at column {}", found, column)
+            }
+            Self::Expected {
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {} at column {}, found {}",
+                expected, column, found
+            ),
+            Self::Unclosed { column } => {
+                write!(f, "parenthesis at column {} is never closed", column)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseInfixError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(BigRational),
+    Var(Symbol),
+    // one of + - * /
+    Op(char),
+    Open,
+    Close,
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Num(n) => write!(f, "{}", n),
+            Token::Var(v) => write!(f, "{:?}", v.as_str()),
+            Token::Op(op) => write!(f, "{:?}", op),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+// Splits the input into tokens, each with the column it starts at.
+fn tokenize(s: &str) -> Result<Vec<(usize, Token)>, ParseInfixError> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let (column, c) = (i + 1, chars[i]);
+        let take_while = |i: &mut usize, f: fn(char) -> bool| {
+            let start = *i;
+            while *i < chars.len() && f(chars[*i]) {
+                *i += 1;
+            }
+            chars[start..*i].iter().collect::<String>()
+        };
+        let token = match c {
+            _ if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            '+' | '-' | '*' | '/' => Token::Op(c),
+            '(' => Token::Open,
+            ')' => Token::Close,
+            _ if c.is_ascii_digit() => {
+                let mut text = take_while(&mut i, |c| c.is_ascii_digit());
+                // only a point followed by a digit is part of the number
+                if i + 1 < chars.len() && chars[i] == '.' && chars[i + 1].is_ascii_digit() {
+                    i += 1;
+                    text += ".";
+                    text += &take_while(&mut i, |c| c.is_ascii_digit());
+                }
+                tokens.push((column, Token::Num(parse_rational(&text).unwrap())));
+                continue;
+            }
+            _ if c.is_alphabetic() || c == '_' => {
+                let name = take_while(&mut i, |c| c.is_alphanumeric() || c == '_');
+                tokens.push((column, Token::Var(Symbol::from(name))));
+                continue;
+            }
+            _ => return Err(ParseInfixError::UnexpectedChar { column, found: c }),
+        };
+        tokens.push((column, token));
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    // the column just past the input, for errors at the end
+    end: usize,
+    expr: RecExpr<Math>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn expected(&self, expected: &'static str) -> ParseInfixError {
+        let (column, found) = match self.tokens.get(self.pos) {
+            Some((column, token)) => (*column, token.to_string()),
+            None => (self.end, "the end of the input".into()),
+        };
+        ParseInfixError::Expected {
+            column,
+            expected,
+            found,
+        }
+    }
+
+    // sum := product (('+' | '-') product)*
+    fn sum(&mut self) -> Result<Id, ParseInfixError> {
+        let mut lhs = self.product()?;
+        while let Some(Token::Op(op @ ('+' | '-'))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            let rhs = self.product()?;
+            lhs = self.expr.add(match op {
+                '+' => Math::Add([lhs, rhs]),
+                _ => Math::Sub([lhs, rhs]),
+            });
+        }
+        Ok(lhs)
+    }
+
+    // product := unary (('*' | '/') unary)*
+    fn product(&mut self) -> Result<Id, ParseInfixError> {
+        let mut lhs = self.unary()?;
+        while let Some(Token::Op(op @ ('*' | '/'))) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            let rhs = self.unary()?;
+            lhs = self.expr.add(match op {
+                '*' => Math::Mul([lhs, rhs]),
+                _ => Math::Div([lhs, rhs]),
+            });
+        }
+        Ok(lhs)
+    }
+
+    // unary := '-' unary | atom
+    fn unary(&mut self) -> Result<Id, ParseInfixError> {
+        if self.peek() != Some(&Token::Op('-')) {
+            return self.atom();
+        }
+        self.pos += 1;
+        // negative numbers are just numbers, and anything else is
+        // multiplied by -1, which is the form the rules use
+        if let Some(Token::Num(n)) = self.peek() {
+            let n = -n.clone();
+            self.pos += 1;
+            return Ok(self.expr.add(Math::Num(n)));
+        }
+        let operand = self.unary()?;
+        let minus_one = self
+            .expr
+            .add(Math::Num(-BigRational::from_integer(1.into())));
+        Ok(self.expr.add(Math::Mul([minus_one, operand])))
+    }
+
+    // atom := number | variable | '(' sum ')'
+    fn atom(&mut self) -> Result<Id, ParseInfixError> {
+        let Some((column, token)) = self.tokens.get(self.pos).cloned() else {
+            return Err(self.expected("a number, variable or '('"));
+        };
+        let node = match token {
+            Token::Num(n) => Math::Num(n),
+            Token::Var(v) => Math::Var(v),
+            Token::Open => {
+                self.pos += 1;
+                let inner = self.sum()?;
+                return match self.peek() {
+                    Some(Token::Close) => {
+                        self.pos += 1;
+                        Ok(inner)
+                    }
+                    None => Err(ParseInfixError::Unclosed { column }),
+                    Some(_) => Err(self.expected("an operator or ')'")),
+                };
+            }
+            _ => return Err(self.expected("a number, variable or '('")),
+        };
+        self.pos += 1;
+        Ok(self.expr.add(node))
+    }
+}
+
+/// Parses a [`Math`] expression written the usual way, like `1 - 2*y/(x + y)`.
+///
+/// `*` and `/` bind tighter than `+` and `-`, all four group to the left,
+/// and a leading `-` negates whatever comes right after it, so `-x*y` is `(-x)*y`.
+/// Numbers are integers or decimals like `0.25`.
+/// There are no fraction literals: `1/3` is always 1 divided by 3,
+/// so `x/1/3` means `(x/1)/3` like you'd expect. It's the same number once it's folded.
+pub fn parse_infix(s: &str) -> Result<RecExpr<Math>, ParseInfixError> {
+    let mut parser = Parser {
+        tokens: tokenize(s)?,
+        pos: 0,
+        end: s.chars().count() + 1,
+        expr: RecExpr::default(),
+    };
+    parser.sum()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(parser.expected("an operator"));
+    }
+    Ok(parser.expr)
+}
+
+/// Parses either an infix expression, as in [`parse_infix`],
+/// or an s-expression like `(- 1 (/ (* 2 y) (+ x y)))`.
+///
+/// Infix is tried first. If that fails and the input isn't an s-expression either,
+/// the error is the one from the infix parser.
+pub fn parse_math(s: &str) -> Result<RecExpr<Math>, ParseInfixError> {
+    parse_infix(s).or_else(|err| s.parse().map_err(|_| err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn infix(s: &str) -> String {
+        parse_infix(s).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_parse_infix() {
+        assert_eq!(infix("1 - 2*y/(x+y)"), "(- 1 (/ (* 2 y) (+ x y)))");
+        assert_eq!(infix("a - b - c"), "(- (- a b) c)");
+        assert_eq!(infix("a / b * c"), "(* (/ a b) c)");
+        assert_eq!(infix("a + b * c"), "(+ a (* b c))");
+        assert_eq!(infix("((a))"), "a");
+
+        // unary minus
+        assert_eq!(infix("-x * y"), "(* (* -1 x) y)");
+        assert_eq!(infix("x - -2"), "(- x -2)");
+        assert_eq!(infix("--x"), "(* -1 (* -1 x))");
+        assert_eq!(infix("2 * -(x + 1)"), "(* 2 (* -1 (+ x 1)))");
+
+        // numbers
+        assert_eq!(infix("0.25 + x_1"), "(+ 1/4 x_1)");
+        assert_eq!(infix("1/3"), "(/ 1 3)");
+        assert_eq!(infix("x/1/3"), "(/ (/ x 1) 3)");
+
+        // s-expressions still work where both are accepted
+        assert_eq!(
+            parse_math("(- 1 (/ (* 2 y) (+ x y)))").unwrap(),
+            parse_infix("1 - 2*y/(x+y)").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = |s: &str| parse_infix(s).unwrap_err().to_string();
+        assert_eq!(err("1 + $"), "unexpected '$' at column 5");
+        assert_eq!(
+            err("1 +"),
+            "expected a number, variable or '(' at column 4, found the end of the input"
+        );
+        assert_eq!(err("(x + y"), "parenthesis at column 1 is never closed");
+        assert_eq!(err("x + y)"), "expected an operator at column 6, found ')'");
+        assert_eq!(err("2 x"), "expected an operator at column 3, found \"x\"");
+        assert_eq!(
+            err("(x y)"),
+            "expected an operator or ')' at column 4, found \"y\""
+        );
+        assert_eq!(parse_infix("* 2").unwrap_err().column(), 1);
+        assert_eq!(parse_math("(+ 1").unwrap_err().column(), 2);
+    }
+}
@@ -0,0 +1,364 @@
+use crate::Lattice;
+
+/// A range of `BITS`-bit machine integers, like an `i32` or a `u8`.
+///
+/// Arithmetic wraps around like it does on the machine, so for a `u8`, `[250, 255] + 10` is `[4, 9]`.
+/// If only part of a range wraps, there's no single range for the result,
+/// so it falls back to every value of the type.
+/// Either way, the result remembers that it [may have overflowed](IntInterval::may_overflow).
+///
+/// Widths go up to 64 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct IntInterval<const BITS: u32, const SIGNED: bool> {
+    // inclusive and within the type, or None if empty
+    range: Option<(i128, i128)>,
+    overflow: bool,
+}
+
+pub type I8Interval = IntInterval<8, true>;
+pub type I16Interval = IntInterval<16, true>;
+pub type I32Interval = IntInterval<32, true>;
+pub type I64Interval = IntInterval<64, true>;
+pub type U8Interval = IntInterval<8, false>;
+pub type U16Interval = IntInterval<16, false>;
+pub type U32Interval = IntInterval<32, false>;
+pub type U64Interval = IntInterval<64, false>;
+
+impl<const BITS: u32, const SIGNED: bool> std::fmt::Display for IntInterval<BITS, SIGNED> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.range {
+            Some((lo, hi)) => write!(f, "[{}, {}]", lo, hi)?,
+            None => write!(f, "empty")?,
+        }
+        if self.overflow {
+            write!(f, " (may overflow)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Every value of the type.
+impl<const BITS: u32, const SIGNED: bool> Default for IntInterval<BITS, SIGNED> {
+    fn default() -> Self {
+        Self::full()
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> IntInterval<BITS, SIGNED> {
+    /// The smallest value of the type.
+    pub const MIN: i128 = {
+        assert!(BITS >= 1 && BITS <= 64, "widths go from 1 to 64 bits");
+        if SIGNED {
+            -(1 << (BITS - 1))
+        } else {
+            0
+        }
+    };
+
+    /// The largest value of the type.
+    pub const MAX: i128 = Self::MIN + (1 << BITS) - 1;
+
+    /// Makes the range from `lo` to `hi`, both included.
+    /// It's empty if `lo > hi`, and panics if either doesn't fit in the type.
+    pub fn new(lo: i128, hi: i128) -> Self {
+        for n in [lo, hi] {
+            assert!(
+                (Self::MIN..=Self::MAX).contains(&n),
+                "{} doesn't fit in {} bits",
+                n,
+                BITS
+            );
+        }
+        Self {
+            range: (lo <= hi).then_some((lo, hi)),
+            overflow: false,
+        }
+    }
+
+    /// Just `n`, wrapped around into the type if it doesn't fit.
+    pub fn singleton(n: i128) -> Self {
+        Self::wrap(Some((n, n)), false)
+    }
+
+    pub fn full() -> Self {
+        Self::new(Self::MIN, Self::MAX)
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            range: None,
+            overflow: false,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.range.is_none()
+    }
+
+    pub fn lo(&self) -> Option<i128> {
+        self.range.map(|(lo, _)| lo)
+    }
+
+    pub fn hi(&self) -> Option<i128> {
+        self.range.map(|(_, hi)| hi)
+    }
+
+    /// Whether some operation that led here might have wrapped around.
+    pub fn may_overflow(&self) -> bool {
+        self.overflow
+    }
+
+    /// The same range, but with the overflow flag set to `overflow`.
+    pub fn with_overflow(self, overflow: bool) -> Self {
+        Self { overflow, ..self }
+    }
+
+    pub fn get_constant(&self) -> Option<i128> {
+        match self.range {
+            Some((lo, hi)) if lo == hi => Some(lo),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, n: i128) -> bool {
+        self.range.is_some_and(|(lo, hi)| lo <= n && n <= hi)
+    }
+
+    pub fn contains_zero(&self) -> bool {
+        self.contains(0)
+    }
+
+    /// The values in both ranges.
+    /// It only may overflow if both did, since either way of getting here is enough.
+    pub fn intersect(&self, other: &Self) -> Self {
+        let range = self.range.zip(other.range).and_then(|((a, b), (c, d))| {
+            let (lo, hi) = (a.max(c), b.min(d));
+            (lo <= hi).then_some((lo, hi))
+        });
+        Self {
+            range,
+            overflow: self.overflow && other.overflow,
+        }
+    }
+
+    /// The smallest range containing both ranges, which may overflow if either did.
+    pub fn hull(&self, other: &Self) -> Self {
+        let range = match (self.range, other.range) {
+            (Some((a, b)), Some((c, d))) => Some((a.min(c), b.max(d))),
+            (range, None) | (None, range) => range,
+        };
+        Self {
+            range,
+            overflow: self.overflow || other.overflow,
+        }
+    }
+
+    // Wraps the exact result of an operation into the type.
+    // `None` means the exact result didn't even fit in an i128.
+    fn wrap(exact: Option<(i128, i128)>, overflow: bool) -> Self {
+        let Some((lo, hi)) = exact else {
+            return Self::full().with_overflow(true);
+        };
+        if Self::MIN <= lo && hi <= Self::MAX {
+            return Self::new(lo, hi).with_overflow(overflow);
+        }
+        // the whole range wraps by the same amount, or it covers the type
+        let modulus = 1 << BITS;
+        let wrap = |n: i128| (n - Self::MIN).rem_euclid(modulus) + Self::MIN;
+        let (lo, hi) = (wrap(lo), wrap(hi));
+        match exact.and_then(|(lo, hi)| hi.checked_sub(lo)) {
+            Some(width) if width < modulus && lo <= hi => Self::new(lo, hi).with_overflow(true),
+            _ => Self::full().with_overflow(true),
+        }
+    }
+
+    // Applies `f` to the bounds of both sides, where the result's bounds are
+    // among the results for each combination of bounds, or None on i128 overflow.
+    fn corners(&self, other: &Self, f: impl Fn(i128, i128) -> Option<i128>) -> Self {
+        let (Some((a, b)), Some((c, d))) = (self.range, other.range) else {
+            return Self::empty();
+        };
+        let corners = [f(a, c), f(a, d), f(b, c), f(b, d)];
+        let exact = corners
+            .iter()
+            .copied()
+            .collect::<Option<Vec<_>>>()
+            .map(|all| {
+                let lo = all.iter().copied().min().unwrap();
+                let hi = all.iter().copied().max().unwrap();
+                (lo, hi)
+            });
+        Self::wrap(exact, self.overflow || other.overflow)
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> Lattice for IntInterval<BITS, SIGNED> {
+    /// Any value, possibly after an overflow.
+    fn top() -> Self {
+        Self::full().with_overflow(true)
+    }
+
+    fn bottom() -> Self {
+        Self::empty()
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        self.intersect(other)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        self.hull(other)
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        let within = match (self.range, other.range) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some((a, b)), Some((c, d))) => c <= a && b <= d,
+        };
+        within && (!self.overflow || other.overflow)
+    }
+}
+
+fn neg<const BITS: u32, const SIGNED: bool>(
+    a: &IntInterval<BITS, SIGNED>,
+) -> IntInterval<BITS, SIGNED> {
+    IntInterval::singleton(0) - a
+}
+
+fn add<const BITS: u32, const SIGNED: bool>(
+    a: &IntInterval<BITS, SIGNED>,
+    b: &IntInterval<BITS, SIGNED>,
+) -> IntInterval<BITS, SIGNED> {
+    a.corners(b, i128::checked_add)
+}
+
+fn sub<const BITS: u32, const SIGNED: bool>(
+    a: &IntInterval<BITS, SIGNED>,
+    b: &IntInterval<BITS, SIGNED>,
+) -> IntInterval<BITS, SIGNED> {
+    a.corners(b, i128::checked_sub)
+}
+
+fn mul<const BITS: u32, const SIGNED: bool>(
+    a: &IntInterval<BITS, SIGNED>,
+    b: &IntInterval<BITS, SIGNED>,
+) -> IntInterval<BITS, SIGNED> {
+    a.corners(b, i128::checked_mul)
+}
+
+impl<const BITS: u32, const SIGNED: bool> std::ops::Neg for &IntInterval<BITS, SIGNED> {
+    type Output = IntInterval<BITS, SIGNED>;
+
+    fn neg(self) -> IntInterval<BITS, SIGNED> {
+        neg(self)
+    }
+}
+
+impl<const BITS: u32, const SIGNED: bool> std::ops::Neg for IntInterval<BITS, SIGNED> {
+    type Output = IntInterval<BITS, SIGNED>;
+
+    fn neg(self) -> IntInterval<BITS, SIGNED> {
+        neg(&self)
+    }
+}
+
+// Same as in interval.rs, auto_ops can't do the const generics.
+macro_rules! impl_binop {
+    ($op:ident, $method:ident, $f:ident) => {
+        impl<const BITS: u32, const SIGNED: bool> std::ops::$op<&IntInterval<BITS, SIGNED>>
+            for &IntInterval<BITS, SIGNED>
+        {
+            type Output = IntInterval<BITS, SIGNED>;
+
+            fn $method(self, other: &IntInterval<BITS, SIGNED>) -> IntInterval<BITS, SIGNED> {
+                $f(self, other)
+            }
+        }
+
+        impl<const BITS: u32, const SIGNED: bool> std::ops::$op<IntInterval<BITS, SIGNED>>
+            for &IntInterval<BITS, SIGNED>
+        {
+            type Output = IntInterval<BITS, SIGNED>;
+
+            fn $method(self, other: IntInterval<BITS, SIGNED>) -> IntInterval<BITS, SIGNED> {
+                $f(self, &other)
+            }
+        }
+
+        impl<const BITS: u32, const SIGNED: bool> std::ops::$op<&IntInterval<BITS, SIGNED>>
+            for IntInterval<BITS, SIGNED>
+        {
+            type Output = IntInterval<BITS, SIGNED>;
+
+            fn $method(self, other: &IntInterval<BITS, SIGNED>) -> IntInterval<BITS, SIGNED> {
+                $f(&self, other)
+            }
+        }
+
+        impl<const BITS: u32, const SIGNED: bool> std::ops::$op<IntInterval<BITS, SIGNED>>
+            for IntInterval<BITS, SIGNED>
+        {
+            type Output = IntInterval<BITS, SIGNED>;
+
+            fn $method(self, other: IntInterval<BITS, SIGNED>) -> IntInterval<BITS, SIGNED> {
+                $f(&self, &other)
+            }
+        }
+    };
+}
+
+impl_binop!(Add, add, add);
+impl_binop!(Sub, sub, sub);
+impl_binop!(Mul, mul, mul);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wraparound() {
+        assert_eq!((U8Interval::MIN, U8Interval::MAX), (0, 255));
+        assert_eq!((I8Interval::MIN, I8Interval::MAX), (-128, 127));
+        assert_eq!(U64Interval::MAX, u64::MAX as i128);
+
+        // in range, nothing overflows
+        let sum = U8Interval::new(1, 2) + U8Interval::new(10, 20);
+        assert_eq!(sum, U8Interval::new(11, 22));
+        assert!(!sum.may_overflow());
+
+        // the whole range wraps together
+        let sum = U8Interval::new(250, 255) + U8Interval::singleton(10);
+        assert_eq!(sum.to_string(), "[4, 9] (may overflow)");
+        assert_eq!((-I8Interval::singleton(-128)).get_constant(), Some(-128));
+        assert_eq!(U8Interval::singleton(300).get_constant(), Some(44));
+
+        // part of it wraps, so it could be anything
+        let sum = U8Interval::new(200, 255) + U8Interval::singleton(10);
+        assert_eq!(sum, U8Interval::full().with_overflow(true));
+        let product = I32Interval::new(0, 1 << 20) * I32Interval::new(0, 1 << 20);
+        assert_eq!(product, I32Interval::top());
+
+        // too big even for an i128
+        let huge = U64Interval::singleton(u64::MAX as i128);
+        assert!((huge * huge * huge).is_top());
+
+        // the flag sticks around
+        let wrapped = U8Interval::singleton(256);
+        assert!((wrapped + U8Interval::singleton(1)).may_overflow());
+        assert!(!(U8Interval::empty() + wrapped).may_overflow());
+    }
+
+    #[test]
+    fn test_lattice() {
+        let a = I16Interval::new(-5, 5);
+        let b = I16Interval::new(0, 10).with_overflow(true);
+        assert_eq!(a.meet(&b), I16Interval::new(0, 5));
+        assert_eq!(a.join(&b), I16Interval::new(-5, 10).with_overflow(true));
+        assert!(a.leq(&a.join(&b)) && b.leq(&a.join(&b)));
+        assert!(!b.leq(&I16Interval::full()));
+        assert!(I16Interval::bottom().leq(&a));
+        assert!(a.intersect(&I16Interval::new(6, 7)).is_empty());
+        assert!(I16Interval::default().contains_zero());
+    }
+}
@@ -1,69 +1,482 @@
-use auto_ops::*;
-use num::{BigRational, Zero};
+use crate::{Bound, Lattice, Rational, Round, Widen};
+use num::{BigInt, BigRational};
+use std::ops::Range;
+use std::str::FromStr;
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Default)]
-pub struct Interval {
-    pub lo: Option<BigRational>, // None represents -inf
-    pub hi: Option<BigRational>, // None represents inf
+/// A range of numbers, bounded by exact [`Rational`]s unless you pick another [`Bound`].
+/// Ranges aren't totally ordered, so there's no `Ord`;
+/// see [`Interval::is_subset_of`] and [`Interval::cmp_lt`] instead.
+#[derive(Debug, PartialEq, Eq, Hash, Clone)]
+pub struct Interval<B = Rational> {
+    // Everything is private so that each interval has just one representation,
+    // which the derived PartialEq and Hash rely on.
+    // See `lo`, `hi`, `lo_open` and `hi_open` for reading them.
+    lo: Option<B>, // None represents -inf
+    hi: Option<B>, // None represents inf
+    // Whether each bound is excluded from the interval.
+    // Infinite bounds are always open, and these stay false for them.
+    lo_open: bool,
+    hi_open: bool,
+    // The empty interval has both bounds set to None.
+    empty: bool,
 }
 
-fn map2(
-    a: &Option<BigRational>,
-    b: &Option<BigRational>,
-    f: impl FnOnce(&BigRational, &BigRational) -> BigRational,
-) -> Option<BigRational> {
+// A bound that overflowed is no bound at all.
+fn finite<B: Bound>(x: B) -> Option<B> {
+    Some(x).filter(Bound::is_finite)
+}
+
+fn map2<B: Bound>(a: &Option<B>, b: &Option<B>, f: impl FnOnce(&B, &B) -> B) -> Option<B> {
     if let (Some(a), Some(b)) = (a.as_ref(), b.as_ref()) {
-        Some(f(a, b))
+        finite(f(a, b))
     } else {
         None
     }
 }
 
+// Picks the tighter of two lower (if `lo`) or upper bounds, with their open flags.
+// Unlike in arithmetic, an infinite bound here just defers to the other side.
+fn tighter<B: Bound>(
+    (a, a_open): (&Option<B>, bool),
+    (b, b_open): (&Option<B>, bool),
+    lo: bool,
+) -> (Option<B>, bool) {
+    match (a, b) {
+        (Some(x), Some(y)) if x == y => (a.clone(), a_open || b_open),
+        (Some(x), Some(y)) if (x > y) == lo => (a.clone(), a_open),
+        (Some(_), None) => (a.clone(), a_open),
+        _ => (b.clone(), b_open),
+    }
+}
+
+// Picks the looser of two lower (if `lo`) or upper bounds, with their open flags.
+fn looser<B: Bound>(
+    (a, a_open): (&Option<B>, bool),
+    (b, b_open): (&Option<B>, bool),
+    lo: bool,
+) -> (Option<B>, bool) {
+    match (a, b) {
+        (Some(x), Some(y)) if x == y => (a.clone(), a_open && b_open),
+        (Some(x), Some(y)) if (x < y) == lo => (a.clone(), a_open),
+        (Some(_), Some(_)) => (b.clone(), b_open),
+        _ => (None, false),
+    }
+}
+
+// Whether bound `a` is at least as tight as `b`, both lower (if `lo`) or upper bounds.
+fn within<B: Bound>(
+    (a, a_open): (&Option<B>, bool),
+    (b, b_open): (&Option<B>, bool),
+    lo: bool,
+) -> bool {
+    match (a, b) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(x), Some(y)) if x == y => a_open || !b_open,
+        (Some(x), Some(y)) => (x > y) == lo,
+    }
+}
+
+// `x^n`, rounded the given way.
+fn pow_rounded<B: Bound>(x: &B, n: u32, round: Round) -> B {
+    // work on |x| so every partial product rounds the same way
+    if x.is_negative() && n % 2 != 0 {
+        return -pow_rounded(&-x.clone(), n, round.flip());
+    }
+    // square and multiply, so huge powers only take a few dozen steps
+    let (mut base, mut n, mut acc) = (x.abs(), n, B::one());
+    while n > 0 {
+        if n % 2 != 0 {
+            acc = acc.mul_rounded(&base, round);
+        }
+        n /= 2;
+        if n > 0 {
+            base = base.mul_rounded(&base, round);
+        }
+    }
+    acc
+}
+
+// A point on the extended real line, only used to multiply bounds.
+// The variant order makes the derived PartialOrd do the right thing.
+#[derive(PartialEq, PartialOrd, Clone)]
+enum Extended<B> {
+    NegInf,
+    Finite(B),
+    PosInf,
+}
+
+impl<B: Bound> Extended<B> {
+    fn lo(bound: &Option<B>) -> Self {
+        bound.clone().map_or(Self::NegInf, Self::Finite)
+    }
+
+    fn hi(bound: &Option<B>) -> Self {
+        bound.clone().map_or(Self::PosInf, Self::Finite)
+    }
+
+    fn finite(self) -> Option<B> {
+        match self {
+            Self::Finite(x) => finite(x),
+            _ => None,
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        matches!(self, Self::Finite(x) if x.is_zero())
+    }
+
+    fn is_negative(&self) -> bool {
+        match self {
+            Self::NegInf => true,
+            Self::Finite(x) => x.is_negative(),
+            Self::PosInf => false,
+        }
+    }
+
+    fn mul(&self, other: &Self, round: Round) -> Self {
+        match (self, other) {
+            (Self::Finite(a), Self::Finite(b)) => Self::Finite(a.mul_rounded(b, round)),
+            // Bounds are only ever approached by finite numbers,
+            // so a bound of exactly 0 kills even an infinite one.
+            (Self::Finite(x), _) | (_, Self::Finite(x)) if x.is_zero() => Self::Finite(B::zero()),
+            _ if self.is_negative() != other.is_negative() => Self::NegInf,
+            _ => Self::PosInf,
+        }
+    }
+
+    // Multiplies two bounds along with whether they are open.
+    fn mul_bound(
+        (a, a_open): &(Self, bool),
+        (b, b_open): &(Self, bool),
+        round: Round,
+    ) -> (Self, bool) {
+        // a closed zero bound is reached, so the zero it produces is too
+        let reaches_zero = (a.is_zero() && !a_open) || (b.is_zero() && !b_open);
+        (a.mul(b, round), !reaches_zero && (*a_open || *b_open))
+    }
+}
+
+/// Parses an interval like `"[0, 1/3)"`, `"(-inf, 0.25]"` or `"empty"`,
+/// panicking if it's malformed. See [`Interval::from_str`] for the fallible version.
+/// For other bounds, use [`Interval::ival`], e.g. `Interval::<f64>::ival("0, 1/3")`.
 pub fn ival(s: &str) -> Interval {
-    let (lo, hi) = s.split_once(',').unwrap();
-    let (lo, hi) = (lo.trim(), hi.trim());
-    Interval {
-        lo: if lo == "-inf" {
-            None
-        } else {
-            Some(lo.parse().unwrap())
-        },
-        hi: if hi == "inf" {
-            None
-        } else {
-            Some(hi.parse().unwrap())
-        },
+    Interval::ival(s)
+}
+
+/// Which bound of an interval something refers to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Lo,
+    Hi,
+}
+
+impl std::fmt::Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Side::Lo => write!(f, "lower"),
+            Side::Hi => write!(f, "upper"),
+        }
     }
 }
 
-impl std::fmt::Display for Interval {
+/// Why a string couldn't be parsed as an [`Interval`].
+/// Offsets are in bytes from the start of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseIntervalError {
+    /// There was no comma separating the two bounds, which start at `offset`.
+    MissingComma { offset: usize },
+    /// The opening bracket at `offset` was never closed.
+    Unclosed { offset: usize },
+    /// The closing bracket at `offset` was never opened.
+    Unopened { offset: usize },
+    /// A second comma showed up at `offset`.
+    ExtraComma { offset: usize },
+    /// A bound was blank.
+    MissingBound { side: Side, offset: usize },
+    /// A bound was neither a number nor an infinity.
+    InvalidNumber {
+        side: Side,
+        offset: usize,
+        text: String,
+    },
+    /// `inf` was given as a lower bound, or `-inf` as an upper bound.
+    WrongInfinity { side: Side, offset: usize },
+    /// The lower bound at `offset` is above the upper bound, both as written.
+    Inverted {
+        offset: usize,
+        lo: String,
+        hi: String,
+    },
+}
+
+impl std::fmt::Display for ParseIntervalError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "(")?;
-        if let Some(lo) = self.lo.as_ref() {
-            lo.fmt(f)?;
-        } else {
-            "-inf".fmt(f)?;
+        match self {
+            Self::MissingComma { offset } => write!(
+                f,
+                "expected a comma between the bounds starting at byte {}",
+                offset
+            ),
+            Self::Unclosed { offset } => write!(f, "unclosed bracket at byte {}", offset),
+            Self::Unopened { offset } => write!(f, "unopened bracket at byte {}", offset),
+            Self::ExtraComma { offset } => write!(f, "unexpected comma at byte {}", offset),
+            Self::MissingBound { side, offset } => {
+                write!(f, "missing {} bound at byte {}", side, offset)
+            }
+            Self::InvalidNumber { side, offset, text } => {
+                write!(
+                    f,
+                    "{} bound {:?} at byte {} is not a number",
+                    side, text, offset
+                )
+            }
+            Self::WrongInfinity { side, offset } => write!(
+                f,
+                "{} bound at byte {} is infinite in the wrong direction",
+                side, offset
+            ),
+            Self::Inverted { offset, lo, hi } => write!(
+                f,
+                "lower bound {} at byte {} is above upper bound {}",
+                lo, offset, hi
+            ),
         }
-        write!(f, ", ")?;
-        if let Some(hi) = self.hi.as_ref() {
-            hi.fmt(f)?;
-        } else {
-            "inf".fmt(f)?;
+    }
+}
+
+impl std::error::Error for ParseIntervalError {}
+
+/// Reads the same notation that [`Display`](std::fmt::Display) writes:
+/// `[` and `]` for closed bounds, `(` and `)` for open ones,
+/// and `empty` (or `∅`) for the empty interval.
+/// The brackets may be left off entirely, in which case both bounds are closed.
+/// Infinite bounds are always open, so `[0, inf]` is the same as `[0, inf)`.
+/// Bounds that an inexact [`Bound`] can't represent are rounded outward.
+impl<B: Bound> FromStr for Interval<B> {
+    type Err = ParseIntervalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let start = s.len() - s.trim_start().len();
+        let end = s.trim_end().len();
+        if matches!(&s[start..end], "empty" | "∅") {
+            return Ok(Interval::empty());
+        }
+
+        let opening = s[start..end].chars().next().filter(|c| "[(".contains(*c));
+        let closing = s[start..end].chars().last().filter(|c| "])".contains(*c));
+        let (lo_open, hi_open, inner) = match (opening, closing) {
+            (None, None) => (false, false, start..end),
+            (Some(o), Some(c)) if end - start > 1 => (o == '(', c == ')', start + 1..end - 1),
+            (Some(_), _) => return Err(ParseIntervalError::Unclosed { offset: start }),
+            (None, Some(_)) => return Err(ParseIntervalError::Unopened { offset: end - 1 }),
+        };
+
+        let comma = inner.start
+            + s[inner.clone()]
+                .find(',')
+                .ok_or(ParseIntervalError::MissingComma {
+                    offset: inner.start,
+                })?;
+        if let Some(extra) = s[comma + 1..inner.end].find(',') {
+            let offset = comma + 1 + extra;
+            return Err(ParseIntervalError::ExtraComma { offset });
+        }
+        let lo = parse_bound(s, inner.start..comma, Side::Lo)?;
+        let hi = parse_bound(s, comma + 1..inner.end, Side::Hi)?;
+        match (lo, hi) {
+            (Some(lo), Some(hi)) if lo > hi => Err(ParseIntervalError::Inverted {
+                offset: comma - s[inner.start..comma].trim_start().len(),
+                lo: s[inner.start..comma].trim().into(),
+                hi: s[comma + 1..inner.end].trim().into(),
+            }),
+            (lo, hi) => Ok(Interval::bounded(
+                lo.and_then(|lo| finite(B::from_rational(&lo, Round::Down))),
+                lo_open,
+                hi.and_then(|hi| finite(B::from_rational(&hi, Round::Up))),
+                hi_open,
+            )),
         }
-        write!(f, ")")
     }
 }
 
-impl Interval {
-    pub fn singleton(n: impl Into<BigRational>) -> Self {
-        let n = n.into();
+impl<B: Bound> TryFrom<&str> for Interval<B> {
+    type Error = ParseIntervalError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+// Parses the bound in `s[range]`, ignoring any surrounding whitespace.
+// Takes the whole string so errors can point at the right byte.
+fn parse_bound(
+    s: &str,
+    range: Range<usize>,
+    side: Side,
+) -> Result<Option<BigRational>, ParseIntervalError> {
+    let untrimmed = &s[range.clone()];
+    let text = untrimmed.trim_start();
+    let offset = range.start + (untrimmed.len() - text.len());
+    let text = text.trim_end();
+    if text.is_empty() {
+        return Err(ParseIntervalError::MissingBound { side, offset });
+    }
+    match (side, text) {
+        (Side::Lo, "-inf") | (Side::Hi, "inf" | "+inf") => Ok(None),
+        (_, "-inf" | "inf" | "+inf") => Err(ParseIntervalError::WrongInfinity { side, offset }),
+        _ => parse_rational(text)
+            .map(Some)
+            .ok_or_else(|| ParseIntervalError::InvalidNumber {
+                side,
+                offset,
+                text: text.into(),
+            }),
+    }
+}
+
+// Parses an integer, a fraction like `-1/3`, or a decimal like `0.25`.
+pub(crate) fn parse_rational(s: &str) -> Option<BigRational> {
+    let Some((int, frac)) = s.split_once('.') else {
+        return s.parse().ok();
+    };
+    let (negative, int) = match int.strip_prefix('-') {
+        Some(int) => (true, int),
+        None => (false, int.strip_prefix('+').unwrap_or(int)),
+    };
+    let all_digits = |t: &str| t.bytes().all(|b| b.is_ascii_digit());
+    if !all_digits(int) || !all_digits(frac) || int.len() + frac.len() == 0 {
+        return None;
+    }
+    let numer: BigInt = format!("{}{}", int, frac).parse().ok()?;
+    let denom = BigInt::from(10).pow(frac.len() as u32);
+    let n = BigRational::new(numer, denom);
+    Some(if negative { -n } else { n })
+}
+
+/// Prints bounds exactly, or as rounded decimals with a precision like `{:.4}`.
+/// See [`Interval::display`] for more options.
+impl<B: Bound> std::fmt::Display for Interval<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.display().fmt(f)
+    }
+}
+
+/// The whole number line, the top of the lattice.
+impl<B> Default for Interval<B> {
+    fn default() -> Self {
+        Self {
+            lo: None,
+            hi: None,
+            lo_open: false,
+            hi_open: false,
+            empty: false,
+        }
+    }
+}
+
+impl<B: Bound> Interval<B> {
+    /// Makes the closed interval from `lo` to `hi`,
+    /// or the empty interval if `lo > hi`.
+    pub fn new(lo: Option<B>, hi: Option<B>) -> Self {
+        Self::bounded(lo, false, hi, false)
+    }
+
+    // Every interval gets built through here, so the open flags
+    // and the empty interval always have a single representation.
+    pub(crate) fn bounded(lo: Option<B>, lo_open: bool, hi: Option<B>, hi_open: bool) -> Self {
+        let lo_open = lo_open && lo.is_some();
+        let hi_open = hi_open && hi.is_some();
+        match (lo.as_ref(), hi.as_ref()) {
+            (Some(l), Some(h)) if l > h || (l == h && (lo_open || hi_open)) => Self::empty(),
+            _ => Self {
+                lo,
+                hi,
+                lo_open,
+                hi_open,
+                empty: false,
+            },
+        }
+    }
+
+    /// The lower bound, or `None` if it's `-inf` or the interval is empty.
+    pub fn lo(&self) -> Option<&B> {
+        self.lo.as_ref()
+    }
+
+    /// The upper bound, or `None` if it's `inf` or the interval is empty.
+    pub fn hi(&self) -> Option<&B> {
+        self.hi.as_ref()
+    }
+
+    /// Whether the lower bound is excluded from the interval.
+    /// Always false for an infinite bound.
+    pub fn lo_open(&self) -> bool {
+        self.lo_open
+    }
+
+    /// Whether the upper bound is excluded from the interval.
+    /// Always false for an infinite bound.
+    pub fn hi_open(&self) -> bool {
+        self.hi_open
+    }
+
+    /// Excludes (or includes) the lower bound, e.g. to say "x > 0".
+    /// Has no effect on an infinite bound.
+    pub fn with_lo_open(self, open: bool) -> Self {
+        if self.is_empty() {
+            return self;
+        }
+        Self::bounded(self.lo, open, self.hi, self.hi_open)
+    }
+
+    /// Excludes (or includes) the upper bound.
+    /// Has no effect on an infinite bound.
+    pub fn with_hi_open(self, open: bool) -> Self {
+        if self.is_empty() {
+            return self;
+        }
+        Self::bounded(self.lo, self.lo_open, self.hi, open)
+    }
+
+    /// The interval containing no numbers at all, the bottom of the lattice.
+    pub fn empty() -> Self {
         Self {
-            lo: Some(n.clone()),
-            hi: Some(n),
+            lo: None,
+            hi: None,
+            lo_open: false,
+            hi_open: false,
+            empty: true,
         }
     }
 
-    pub fn get_constant(&self) -> Option<&BigRational> {
+    pub fn is_empty(&self) -> bool {
+        self.empty
+    }
+
+    /// Like the free function [`ival`], but for any kind of bound.
+    pub fn ival(s: &str) -> Self {
+        s.parse()
+            .unwrap_or_else(|e| panic!("bad interval {:?}: {}", s, e))
+    }
+
+    pub fn singleton(n: impl Into<B>) -> Self {
+        let n = n.into();
+        Self::new(Some(n.clone()), Some(n))
+    }
+
+    /// The tightest interval around `n`, which is just `n` if the bound can represent it.
+    pub fn from_rational(n: &BigRational) -> Self {
+        Self::new(
+            finite(B::from_rational(n, Round::Down)),
+            finite(B::from_rational(n, Round::Up)),
+        )
+    }
+
+    pub fn get_constant(&self) -> Option<&B> {
+        if self.is_empty() {
+            return None;
+        }
         match (self.lo.as_ref(), self.hi.as_ref()) {
             (Some(lo), Some(hi)) if lo == hi => Some(lo),
             _ => None,
@@ -71,90 +484,630 @@ impl Interval {
     }
 
     pub fn from_f64s(lo: f64, hi: f64) -> Self {
-        Self {
-            lo: if lo == f64::NEG_INFINITY {
-                None
-            } else {
-                Some(BigRational::from_float(lo).unwrap())
-            },
-            hi: if hi == f64::INFINITY {
-                None
-            } else {
-                Some(BigRational::from_float(hi).unwrap())
-            },
-        }
+        let bound = |x: f64, round| finite(B::from_rational(&BigRational::from_float(x)?, round));
+        Self::new(bound(lo, Round::Down), bound(hi, Round::Up))
     }
 
+    /// The numbers in both intervals.
+    /// Returns the empty interval if they are disjoint.
     pub fn intersect(&self, other: &Self) -> Self {
-        Self {
-            lo: map2(&self.lo, &other.lo, |a, b| a.max(b).clone()),
-            hi: map2(&self.hi, &other.hi, |a, b| a.min(b).clone()),
+        if self.is_empty() || other.is_empty() {
+            return Self::empty();
+        }
+        let (lo, lo_open) = tighter((&self.lo, self.lo_open), (&other.lo, other.lo_open), true);
+        let (hi, hi_open) = tighter((&self.hi, self.hi_open), (&other.hi, other.hi_open), false);
+        Self::bounded(lo, lo_open, hi, hi_open)
+    }
+
+    /// The smallest interval containing both intervals,
+    /// e.g. the range of an if-then-else.
+    pub fn hull(&self, other: &Self) -> Self {
+        if self.is_empty() {
+            return other.clone();
+        }
+        if other.is_empty() {
+            return self.clone();
         }
+        let (lo, lo_open) = looser((&self.lo, self.lo_open), (&other.lo, other.lo_open), true);
+        let (hi, hi_open) = looser((&self.hi, self.hi_open), (&other.hi, other.hi_open), false);
+        Self::bounded(lo, lo_open, hi, hi_open)
     }
 
-    pub fn contains(&self, n: &BigRational) -> bool {
-        self.lo.as_ref().map_or(true, |lo| lo <= n) && self.hi.as_ref().map_or(true, |hi| n <= hi)
+    pub fn contains(&self, n: &B) -> bool {
+        !self.is_empty()
+            && self
+                .lo
+                .as_ref()
+                .is_none_or(|lo| lo < n || (lo == n && !self.lo_open))
+            && self
+                .hi
+                .as_ref()
+                .is_none_or(|hi| n < hi || (n == hi && !self.hi_open))
     }
 
     pub fn contains_zero(&self) -> bool {
-        self.contains(&BigRational::zero())
+        self.contains(&B::zero())
     }
 
     pub fn recip(&self) -> Self {
+        if self.is_empty() {
+            return Self::empty();
+        }
         if self.contains_zero() {
             return Self::default();
         }
 
-        let safe_recip = |x: &Option<BigRational>| match x.as_ref() {
+        let safe_recip = |x: &Option<B>, round| match x.as_ref() {
             Some(x) if x.is_zero() => None,
-            Some(x) => Some(x.recip()),
-            None => Some(BigRational::zero()),
+            Some(x) => finite(B::one().div_rounded(x, round)),
+            None => Some(B::zero()),
         };
 
-        Self {
-            lo: safe_recip(&self.hi),
-            hi: safe_recip(&self.lo),
+        // the reciprocal of an infinite bound is a zero that is never reached
+        Self::bounded(
+            safe_recip(&self.hi, Round::Down),
+            self.hi_open || self.hi.is_none(),
+            safe_recip(&self.lo, Round::Up),
+            self.lo_open || self.lo.is_none(),
+        )
+    }
+
+    // Applies an increasing function to both bounds,
+    // rounding the lower one down and the upper one up.
+    fn map_increasing(&self, f: impl Fn(&B, Round) -> B) -> Self {
+        if self.is_empty() {
+            return Self::empty();
         }
+        Self::bounded(
+            self.lo.as_ref().and_then(|lo| finite(f(lo, Round::Down))),
+            self.lo_open,
+            self.hi.as_ref().and_then(|hi| finite(f(hi, Round::Up))),
+            self.hi_open,
+        )
+    }
+
+    /// The absolute values of everything in the interval.
+    pub fn abs(&self) -> Self {
+        let non_negative = Self::new(Some(B::zero()), None);
+        let non_positive = Self::new(None, Some(B::zero()));
+        self.intersect(&non_negative)
+            .hull(&-self.intersect(&non_positive))
+    }
+
+    /// `x * x` for each `x` in the interval,
+    /// which is tighter than `self * self` since both sides are the same `x`.
+    pub fn square(&self) -> Self {
+        self.powi(2)
+    }
+
+    /// Raises every number in the interval to the `n`th power.
+    /// Negative powers divide, so they give the whole line if the interval contains zero.
+    pub fn powi(&self, n: i32) -> Self {
+        let pow = |x: &B, round| pow_rounded(x, n.unsigned_abs(), round);
+        let powered = match n {
+            // every x^0 is exactly 1, 0^0 included, whatever the bounds look like
+            0 if self.is_empty() => return Self::empty(),
+            0 => return Self::singleton(B::one()),
+            // odd powers are increasing everywhere
+            _ if n % 2 != 0 => self.map_increasing(pow),
+            // even powers are only increasing from zero up
+            _ => self.abs().map_increasing(pow),
+        };
+        if n < 0 {
+            powered.recip()
+        } else {
+            powered
+        }
+    }
+
+    /// Widens the interval a little so its bounds are cheaper to compute with.
+    /// Rational bounds with more than `bits` bits in the numerator or denominator
+    /// get rounded outward to about `bits` significant bits, so this only ever grows the interval.
+    pub fn simplify(&self, bits: u32) -> Self {
+        self.map_increasing(|x, round| x.simplify(bits, round))
     }
 }
 
-impl_op_ex!(+ |a: &Interval, b: &Interval| -> Interval {
-    Interval {
-        lo: map2(&a.lo, &b.lo, |a, b| a + b),
-        hi: map2(&a.hi, &b.hi, |a, b| a + b),
+impl<B: Bound> Lattice for Interval<B> {
+    fn top() -> Self {
+        Self::default()
     }
-});
 
-impl_op_ex!(-|a: &Interval, b: &Interval| -> Interval {
-    Interval {
-        lo: map2(&a.lo, &b.hi, |a, b| a - b),
-        hi: map2(&a.hi, &b.lo, |a, b| a - b),
+    fn bottom() -> Self {
+        Self::empty()
     }
-});
 
-impl_op_ex!(*|a: &Interval, b: &Interval| -> Interval {
-    let compute_possible = || -> Option<[BigRational; 4]> {
-        Some([
-            map2(&a.lo, &b.lo, |a, b| a * b)?,
-            map2(&a.lo, &b.hi, |a, b| a * b)?,
-            map2(&a.hi, &b.lo, |a, b| a * b)?,
-            map2(&a.hi, &b.hi, |a, b| a * b)?,
-        ])
+    fn meet(&self, other: &Self) -> Self {
+        self.intersect(other)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        self.hull(other)
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        !other.is_empty()
+            && within((&self.lo, self.lo_open), (&other.lo, other.lo_open), true)
+            && within((&self.hi, self.hi_open), (&other.hi, other.hi_open), false)
+    }
+}
+
+impl<B: Bound> Widen for Interval<B> {
+    /// Any bound that moved goes straight to infinity.
+    fn widen(&self, next: &Self) -> Self {
+        self.widen_with_thresholds(next, &[])
+    }
+
+    /// Only infinite bounds get refined, so this takes at most two steps to settle.
+    fn narrow(&self, next: &Self) -> Self {
+        if self.is_empty() || next.is_empty() {
+            return Self::empty();
+        }
+        let pick = |a: &Option<B>, a_open, b: &Option<B>, b_open| match a {
+            None => (b.clone(), b_open),
+            Some(_) => (a.clone(), a_open),
+        };
+        let (lo, lo_open) = pick(&self.lo, self.lo_open, &next.lo, next.lo_open);
+        let (hi, hi_open) = pick(&self.hi, self.hi_open, &next.hi, next.hi_open);
+        Self::bounded(lo, lo_open, hi, hi_open)
+    }
+}
+
+impl<B: Bound> Interval<B> {
+    /// Like [`Widen::widen`], but a bound that moved stops at the nearest of the `thresholds`
+    /// beyond it before going to infinity, e.g. at 0 or at the size of an array.
+    /// There are only so many thresholds, so this still terminates.
+    pub fn widen_with_thresholds(&self, next: &Self, thresholds: &[B]) -> Self {
+        if self.is_empty() {
+            return next.clone();
+        }
+        if next.is_empty() {
+            return self.clone();
+        }
+        let cmp = |a: &&B, b: &&B| a.partial_cmp(b).unwrap();
+        let (lo, lo_open) = if within((&next.lo, next.lo_open), (&self.lo, self.lo_open), true) {
+            (self.lo.clone(), self.lo_open)
+        } else {
+            let below = |lo: &B| thresholds.iter().filter(|t| *t <= lo).max_by(cmp).cloned();
+            (next.lo.as_ref().and_then(below), false)
+        };
+        let (hi, hi_open) = if within((&next.hi, next.hi_open), (&self.hi, self.hi_open), false) {
+            (self.hi.clone(), self.hi_open)
+        } else {
+            let above = |hi: &B| thresholds.iter().filter(|t| *t >= hi).min_by(cmp).cloned();
+            (next.hi.as_ref().and_then(above), false)
+        };
+        Self::bounded(lo, lo_open, hi, hi_open)
+    }
+}
+
+/// The answer to a question about every pair of numbers drawn from two intervals.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Certainty {
+    /// It holds no matter which numbers are picked.
+    True,
+    /// It fails no matter which numbers are picked.
+    False,
+    /// It depends on which numbers are picked.
+    Unknown,
+}
+
+impl Certainty {
+    pub fn is_true(self) -> bool {
+        self == Certainty::True
+    }
+
+    pub fn is_false(self) -> bool {
+        self == Certainty::False
+    }
+
+    // True wins over false, so check that first.
+    fn decide(certainly: bool, certainly_not: bool) -> Self {
+        match (certainly, certainly_not) {
+            (true, _) => Certainty::True,
+            (_, true) => Certainty::False,
+            _ => Certainty::Unknown,
+        }
+    }
+}
+
+// Whether everything below upper bound `hi` is strictly below everything above lower bound `lo`.
+fn below<B: Bound>(
+    (hi, hi_open): (&Extended<B>, bool),
+    (lo, lo_open): (&Extended<B>, bool),
+) -> bool {
+    hi < lo || (hi == lo && (hi_open || lo_open))
+}
+
+impl<B: Bound> Interval<B> {
+    /// Whether every number in `self` is also in `other`, same as [`Lattice::leq`].
+    pub fn is_subset_of(&self, other: &Self) -> bool {
+        self.leq(other)
+    }
+
+    /// Whether some number is in both intervals.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        !self.intersect(other).is_empty()
+    }
+
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        !self.overlaps(other)
+    }
+
+    // The bounds as points on the extended line, along with their open flags.
+    fn extended(&self) -> ((Extended<B>, bool), (Extended<B>, bool)) {
+        (
+            (Extended::lo(&self.lo), self.lo_open),
+            (Extended::hi(&self.hi), self.hi_open),
+        )
+    }
+
+    /// Whether `x < y` for `x` in `self` and `y` in `other`.
+    /// Nothing is known about the empty interval, so that's always [`Certainty::Unknown`].
+    pub fn cmp_lt(&self, other: &Self) -> Certainty {
+        if self.is_empty() || other.is_empty() {
+            return Certainty::Unknown;
+        }
+        let ((a_lo, _), a_hi) = self.extended();
+        let (b_lo, (b_hi, _)) = other.extended();
+        Certainty::decide(below((&a_hi.0, a_hi.1), (&b_lo.0, b_lo.1)), b_hi <= a_lo)
+    }
+
+    /// Whether `x <= y` for `x` in `self` and `y` in `other`.
+    pub fn cmp_le(&self, other: &Self) -> Certainty {
+        if self.is_empty() || other.is_empty() {
+            return Certainty::Unknown;
+        }
+        let (a_lo, (a_hi, _)) = self.extended();
+        let ((b_lo, _), b_hi) = other.extended();
+        Certainty::decide(a_hi <= b_lo, below((&b_hi.0, b_hi.1), (&a_lo.0, a_lo.1)))
+    }
+
+    /// Whether `x == y` for `x` in `self` and `y` in `other`,
+    /// which is only certain when both are the same single number.
+    pub fn cmp_eq(&self, other: &Self) -> Certainty {
+        if self.is_empty() || other.is_empty() {
+            return Certainty::Unknown;
+        }
+        let same_constant =
+            self.get_constant().is_some() && self.get_constant() == other.get_constant();
+        Certainty::decide(same_constant, self.is_disjoint(other))
+    }
+}
+
+/// How spread out an interval is, for comparing how tight two intervals are.
+/// Smaller is tighter, and infinite bounds sort after every finite width.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Spread<B = Rational> {
+    Empty,
+    Finite(B),
+    /// Only one of the bounds is infinite.
+    HalfInfinite,
+    Infinite,
+}
+
+impl<B: Bound> std::fmt::Display for Spread<B> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Spread::Empty => write!(f, "empty"),
+            Spread::Finite(width) => write!(f, "{}", width),
+            Spread::HalfInfinite => write!(f, "half-infinite"),
+            Spread::Infinite => write!(f, "infinite"),
+        }
+    }
+}
+
+impl<B: Bound> Interval<B> {
+    /// `hi - lo`, or `None` if the interval is empty or unbounded.
+    pub fn width(&self) -> Option<B> {
+        match (self.lo.as_ref(), self.hi.as_ref()) {
+            (Some(lo), Some(hi)) if !self.is_empty() => finite(hi.sub_rounded(lo, Round::Up)),
+            _ => None,
+        }
+    }
+
+    /// The number halfway between the bounds, or `None` if the interval is empty or unbounded.
+    /// It never falls outside the bounds, even if it had to be rounded.
+    pub fn midpoint(&self) -> Option<B> {
+        match (self.lo.as_ref(), self.hi.as_ref()) {
+            (Some(lo), Some(hi)) if !self.is_empty() => {
+                // halving first can't overflow
+                let two = B::one() + B::one();
+                let mid = lo
+                    .div_rounded(&two, Round::Down)
+                    .add_rounded(&hi.div_rounded(&two, Round::Down), Round::Down);
+                Some(if &mid < lo { lo.clone() } else { mid })
+            }
+            _ => None,
+        }
+    }
+
+    /// The distance from the midpoint to the farther bound,
+    /// so the interval fits inside `midpoint ± radius`.
+    pub fn radius(&self) -> Option<B> {
+        let mid = self.midpoint()?;
+        let below = mid.sub_rounded(self.lo.as_ref()?, Round::Up);
+        let above = self.hi.as_ref()?.sub_rounded(&mid, Round::Up);
+        finite(if below > above { below } else { above })
+    }
+
+    /// The largest absolute value in the interval,
+    /// or `None` if it's empty or unbounded.
+    pub fn mag(&self) -> Option<B> {
+        if self.is_empty() {
+            return None;
+        }
+        let (lo, hi) = (self.lo.as_ref()?.abs(), self.hi.as_ref()?.abs());
+        Some(if lo > hi { lo } else { hi })
+    }
+
+    /// The smallest absolute value in the interval (or how close it gets, if that bound is open),
+    /// or `None` if it's empty.
+    pub fn mig(&self) -> Option<B> {
+        if self.is_empty() {
+            return None;
+        }
+        Some(match (self.lo.as_ref(), self.hi.as_ref()) {
+            (Some(lo), _) if lo.is_positive() => lo.clone(),
+            (_, Some(hi)) if hi.is_negative() => hi.abs(),
+            _ => B::zero(),
+        })
+    }
+
+    /// How tight the interval is, comparable even when bounds are infinite.
+    pub fn spread(&self) -> Spread<B> {
+        if self.is_empty() {
+            return Spread::Empty;
+        }
+        match (self.lo.is_some(), self.hi.is_some()) {
+            (true, true) => self.width().map_or(Spread::Infinite, Spread::Finite),
+            (false, false) => Spread::Infinite,
+            _ => Spread::HalfInfinite,
+        }
+    }
+}
+
+impl<B: Bound> Interval<B> {
+    /// Splits the interval in two at its midpoint, with both halves sharing it.
+    /// Returns `None` if there's nothing to split: the interval is empty,
+    /// unbounded, or too narrow to have a point strictly inside.
+    pub fn bisect(&self) -> Option<(Self, Self)> {
+        let mid = self.midpoint()?;
+        if Some(&mid) == self.lo.as_ref() || Some(&mid) == self.hi.as_ref() {
+            return None;
+        }
+        Some((
+            Self::bounded(self.lo.clone(), self.lo_open, Some(mid.clone()), false),
+            Self::bounded(Some(mid), false, self.hi.clone(), self.hi_open),
+        ))
+    }
+
+    /// Splits the interval into `n` pieces of (nearly) equal width that cover it,
+    /// neighbors sharing their endpoints. Intervals that can't be split come back whole.
+    pub fn split_n(&self, n: usize) -> Vec<Self> {
+        let (Some(lo), Some(hi), Some(width)) = (self.lo.as_ref(), self.hi.as_ref(), self.width())
+        else {
+            return vec![self.clone()];
+        };
+        if n <= 1 || lo == hi {
+            return vec![self.clone()];
+        }
+
+        // rounding can only pull a cut back towards the previous one,
+        // and the last cut is exactly `hi`, so the pieces always cover everything
+        let mut cuts = vec![lo.clone()];
+        for i in 1..n {
+            let fraction = B::from_rational(&BigRational::new(i.into(), n.into()), Round::Down);
+            let cut = lo.add_rounded(&width.mul_rounded(&fraction, Round::Down), Round::Down);
+            if &cut > cuts.last().unwrap() && &cut < hi {
+                cuts.push(cut);
+            }
+        }
+        cuts.push(hi.clone());
+
+        let last = cuts.len() - 2;
+        cuts.windows(2)
+            .enumerate()
+            .map(|(i, cut)| {
+                let lo_open = i == 0 && self.lo_open;
+                let hi_open = i == last && self.hi_open;
+                Self::bounded(Some(cut[0].clone()), lo_open, Some(cut[1].clone()), hi_open)
+            })
+            .collect()
+    }
+}
+
+/// Backward contractors, in the style of HC4-revise.
+///
+/// Each takes `self` as the range of a result `c = a op b`
+/// and shrinks the ranges of the operands to the values that could have produced it.
+/// Anything left out couldn't have, so this is always sound, if not always tight.
+/// If an operand comes back empty, no values of the operands could produce `c` at all.
+impl<B: Bound> Interval<B> {
+    /// `c = a + b`, so `a = c - b` and `b = c - a`.
+    pub fn contract_add(&self, a: &Self, b: &Self) -> (Self, Self) {
+        let a = a.intersect(&(self - b));
+        let b = b.intersect(&(self - &a));
+        (a, b)
+    }
+
+    /// `c = a - b`, so `a = c + b` and `b = a - c`.
+    pub fn contract_sub(&self, a: &Self, b: &Self) -> (Self, Self) {
+        let a = a.intersect(&(self + b));
+        let b = b.intersect(&(&a - self));
+        (a, b)
+    }
+
+    /// `c = a * b`, so `a = c / b` and `b = c / a`.
+    pub fn contract_mul(&self, a: &Self, b: &Self) -> (Self, Self) {
+        let a = a.intersect(&self.div_unless_zero(b));
+        let b = b.intersect(&self.div_unless_zero(&a));
+        (a, b)
+    }
+
+    /// `c = a / b`, so `a = c * b` and `b = a / c`.
+    pub fn contract_div(&self, a: &Self, b: &Self) -> (Self, Self) {
+        let a = a.intersect(&(self * b));
+        let b = b.intersect(&a.div_unless_zero(self));
+        (a, b)
+    }
+
+    // Undoing a multiplication by zero says nothing at all, since 0 * x = 0 for every x.
+    // Plain division would say [0, 0] / [0, 0] = [0, 0] instead.
+    fn div_unless_zero(&self, other: &Self) -> Self {
+        if other.contains_zero() {
+            Self::default()
+        } else {
+            self / other
+        }
+    }
+
+    /// `c = 1 / a`, so `a = 1 / c`.
+    pub fn contract_recip(&self, a: &Self) -> Self {
+        a.intersect(&self.recip())
+    }
+}
+
+fn neg<B: Bound>(a: &Interval<B>) -> Interval<B> {
+    if a.is_empty() {
+        return Interval::empty();
+    }
+    Interval::bounded(
+        a.hi.as_ref().map(|hi| -hi.clone()),
+        a.hi_open,
+        a.lo.as_ref().map(|lo| -lo.clone()),
+        a.lo_open,
+    )
+}
+
+fn add<B: Bound>(a: &Interval<B>, b: &Interval<B>) -> Interval<B> {
+    if a.is_empty() || b.is_empty() {
+        return Interval::empty();
+    }
+    Interval::bounded(
+        map2(&a.lo, &b.lo, |a, b| a.add_rounded(b, Round::Down)),
+        a.lo_open || b.lo_open,
+        map2(&a.hi, &b.hi, |a, b| a.add_rounded(b, Round::Up)),
+        a.hi_open || b.hi_open,
+    )
+}
+
+fn sub<B: Bound>(a: &Interval<B>, b: &Interval<B>) -> Interval<B> {
+    if a.is_empty() || b.is_empty() {
+        return Interval::empty();
+    }
+    Interval::bounded(
+        map2(&a.lo, &b.hi, |a, b| a.sub_rounded(b, Round::Down)),
+        a.lo_open || b.hi_open,
+        map2(&a.hi, &b.lo, |a, b| a.sub_rounded(b, Round::Up)),
+        a.hi_open || b.lo_open,
+    )
+}
+
+fn mul<B: Bound>(a: &Interval<B>, b: &Interval<B>) -> Interval<B> {
+    if a.is_empty() || b.is_empty() {
+        return Interval::empty();
+    }
+    let (a_lo, a_hi) = (
+        (Extended::lo(&a.lo), a.lo_open),
+        (Extended::hi(&a.hi), a.hi_open),
+    );
+    let (b_lo, b_hi) = (
+        (Extended::lo(&b.lo), b.lo_open),
+        (Extended::hi(&b.hi), b.hi_open),
+    );
+    let possible = |round| {
+        [
+            Extended::mul_bound(&a_lo, &b_lo, round),
+            Extended::mul_bound(&a_lo, &b_hi, round),
+            Extended::mul_bound(&a_hi, &b_lo, round),
+            Extended::mul_bound(&a_hi, &b_hi, round),
+        ]
     };
+    // bounds are never NaN, so the comparisons always succeed
+    let cmp = |x: &Extended<B>, y: &Extended<B>| x.partial_cmp(y).unwrap();
+    // on ties, prefer the closed bound since that value is reached
+    let (lo, lo_open) = possible(Round::Down)
+        .into_iter()
+        .min_by(|(x, x_open), (y, y_open)| cmp(x, y).then(x_open.cmp(y_open)))
+        .unwrap();
+    let (hi, hi_open) = possible(Round::Up)
+        .into_iter()
+        .max_by(|(x, x_open), (y, y_open)| cmp(x, y).then(y_open.cmp(x_open)))
+        .unwrap();
+    Interval::bounded(lo.finite(), lo_open, hi.finite(), hi_open)
+}
+
+fn div<B: Bound>(a: &Interval<B>, b: &Interval<B>) -> Interval<B> {
+    mul(a, &b.recip())
+}
+
+impl<B: Bound> std::ops::Neg for &Interval<B> {
+    type Output = Interval<B>;
+
+    fn neg(self) -> Interval<B> {
+        neg(self)
+    }
+}
+
+impl<B: Bound> std::ops::Neg for Interval<B> {
+    type Output = Interval<B>;
+
+    fn neg(self) -> Interval<B> {
+        neg(&self)
+    }
+}
 
-    compute_possible().map_or_else(Interval::default, |possible| Interval {
-        lo: possible.iter().min().cloned(),
-        hi: possible.iter().max().cloned(),
-    })
-});
+// auto_ops can't do generics, so this stamps out the
+// owned and borrowed versions of each operator by hand.
+macro_rules! impl_binop {
+    ($op:ident, $method:ident, $f:ident) => {
+        impl<B: Bound> std::ops::$op<&Interval<B>> for &Interval<B> {
+            type Output = Interval<B>;
+
+            fn $method(self, other: &Interval<B>) -> Interval<B> {
+                $f(self, other)
+            }
+        }
+
+        impl<B: Bound> std::ops::$op<Interval<B>> for &Interval<B> {
+            type Output = Interval<B>;
+
+            fn $method(self, other: Interval<B>) -> Interval<B> {
+                $f(self, &other)
+            }
+        }
 
-impl_op_ex!(/ |a: &Interval, b: &Interval| -> Interval {
-    a * b.recip()
-});
+        impl<B: Bound> std::ops::$op<&Interval<B>> for Interval<B> {
+            type Output = Interval<B>;
+
+            fn $method(self, other: &Interval<B>) -> Interval<B> {
+                $f(&self, other)
+            }
+        }
+
+        impl<B: Bound> std::ops::$op<Interval<B>> for Interval<B> {
+            type Output = Interval<B>;
+
+            fn $method(self, other: Interval<B>) -> Interval<B> {
+                $f(&self, &other)
+            }
+        }
+    };
+}
+
+impl_binop!(Add, add, add);
+impl_binop!(Sub, sub, sub);
+impl_binop!(Mul, mul, mul);
+impl_binop!(Div, div, div);
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use num::{One, Zero};
 
     #[test]
     fn test_contains() {
@@ -164,6 +1117,519 @@ mod tests {
         assert!(!ival("10, inf").contains_zero());
     }
 
+    #[test]
+    fn test_empty() {
+        let disjoint = ival("0, 1").intersect(&ival("2, 3"));
+        assert!(disjoint.is_empty());
+        assert_eq!(disjoint, Interval::empty());
+        assert_eq!(
+            Interval::new(Some(BigRational::one()), Some(BigRational::zero())),
+            Interval::empty()
+        );
+        assert!(!disjoint.contains_zero());
+        assert_eq!(disjoint.get_constant(), None);
+        assert_eq!((disjoint.lo(), disjoint.hi()), (None, None));
+        assert_eq!(disjoint.to_string(), "empty");
+        assert_eq!(ival("empty"), disjoint);
+
+        // emptiness propagates through all the arithmetic
+        let x = ival("-1, 1");
+        assert!((&x + &disjoint).is_empty());
+        assert!((&disjoint - &x).is_empty());
+        assert!((&x * &disjoint).is_empty());
+        assert!((&x / &disjoint).is_empty());
+        assert!(disjoint.recip().is_empty());
+
+        // touching intervals still share a point
+        assert_eq!(ival("0, 1").intersect(&ival("1, 2")), ival("1, 1"));
+        assert_eq!(ival("0, 1").intersect(&Interval::default()), ival("0, 1"));
+    }
+
+    #[test]
+    fn test_unbounded_mul() {
+        assert_eq!(ival("1, inf") * ival("1, 2"), ival("1, inf"));
+        assert_eq!(ival("-inf, -1") * ival("2, 3"), ival("-inf, -2"));
+        assert_eq!(ival("-inf, -1") * ival("-3, -2"), ival("2, inf"));
+        assert_eq!(ival("0, 1") * ival("1, inf"), ival("0, inf"));
+        assert_eq!(ival("-1, 0") * ival("1, inf"), ival("-inf, 0"));
+        assert_eq!(ival("-1, 1") * ival("1, inf"), Interval::default());
+        assert_eq!(ival("0, 0") * Interval::default(), ival("0, 0"));
+        assert_eq!(ival("0, inf") * ival("0, inf"), ival("0, inf"));
+
+        // and they survive division too
+        assert_eq!(
+            ival("1, 2") / ival("1, inf"),
+            ival("0, 2").with_lo_open(true)
+        );
+        assert_eq!(ival("1, inf") / ival("2, 4"), ival("1/4, inf"));
+    }
+
+    #[test]
+    fn test_open_bounds() {
+        let pos = ival("0, 5").with_lo_open(true);
+        assert!(!pos.contains_zero());
+        assert!(pos.contains(&Rational::new(1, 100)));
+        assert_eq!(pos.recip(), ival("1/5, inf"));
+        assert_eq!(ival("-5, 0").with_hi_open(true).recip(), ival("-inf, -1/5"));
+        assert_eq!(ival("1, inf").recip(), ival("0, 1").with_lo_open(true));
+
+        // a point with an open side is empty
+        assert!(ival("1, 1").with_hi_open(true).is_empty());
+        assert_eq!(ival("-inf, 1").with_lo_open(true), ival("-inf, 1"));
+        assert_eq!(pos.lo(), Some(&Rational::zero()));
+        assert_eq!(ival("-inf, 1").hi(), Some(&Rational::one()));
+        assert_eq!(ival("-inf, 1").lo(), None);
+
+        let open = ival("0, 1").with_lo_open(true).with_hi_open(true);
+        assert!(ival("0, 1")
+            .intersect(&ival("1, 2").with_lo_open(true))
+            .is_empty());
+        assert_eq!(open.intersect(&ival("0, 1")), open);
+        assert_eq!(
+            &open + &ival("1, 1"),
+            ival("1, 2").with_lo_open(true).with_hi_open(true)
+        );
+        assert_eq!(
+            &open - &ival("0, 1"),
+            ival("-1, 1").with_lo_open(true).with_hi_open(true)
+        );
+
+        // open bounds mostly stay open through multiplication,
+        // but a closed zero bound is always reached
+        assert_eq!(&pos * &ival("1, 2"), ival("0, 10").with_lo_open(true));
+        assert_eq!(&pos * &ival("0, 2"), ival("0, 10"));
+        assert_eq!(&pos * &ival("-1, 1"), ival("-5, 5"));
+        assert_eq!(&pos * &ival("1, inf"), ival("0, inf").with_lo_open(true));
+        assert_eq!(&open * &open, open);
+        assert_eq!(ival("2, 2") / &pos, ival("2/5, inf"));
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(ival("0,1/3"), ival("  0 ,\t1/3 "));
+        assert_eq!(ival("0.25, 1.5"), ival("1/4, 3/2"));
+        assert_eq!(ival("-.5, +2."), ival("-1/2, 2"));
+        assert_eq!(ival("-inf, +inf"), Interval::default());
+        assert_eq!(ival("-inf, inf"), Interval::default());
+        assert_eq!(Interval::try_from("1, inf"), Ok(ival("1, inf")));
+
+        use ParseIntervalError::*;
+        let err = |s: &str| s.parse::<Interval>().unwrap_err();
+        assert_eq!(err("0 1"), MissingComma { offset: 0 });
+        assert_eq!(err("0, 1, 2"), ExtraComma { offset: 4 });
+        let (lo, hi) = (Side::Lo, Side::Hi);
+        assert_eq!(
+            err(" , 1"),
+            MissingBound {
+                side: lo,
+                offset: 1
+            }
+        );
+        assert_eq!(
+            err("0,  "),
+            MissingBound {
+                side: hi,
+                offset: 2
+            }
+        );
+        assert_eq!(
+            err("inf, 1"),
+            WrongInfinity {
+                side: lo,
+                offset: 0
+            }
+        );
+        assert_eq!(
+            err("0, -inf"),
+            WrongInfinity {
+                side: hi,
+                offset: 3
+            }
+        );
+        assert_eq!(
+            err("0,  1/x"),
+            InvalidNumber {
+                side: hi,
+                offset: 4,
+                text: "1/x".into()
+            }
+        );
+        assert_eq!(
+            err("1.2.3, 4"),
+            InvalidNumber {
+                side: lo,
+                offset: 0,
+                text: "1.2.3".into()
+            }
+        );
+        assert_eq!(
+            err("2, 1.5"),
+            Inverted {
+                offset: 0,
+                lo: "2".into(),
+                hi: "1.5".into()
+            }
+        );
+        assert_eq!(
+            err("[ 2, 1.5]").to_string(),
+            "lower bound 2 at byte 2 is above upper bound 1.5"
+        );
+        assert_eq!(
+            err("0,  1/x").to_string(),
+            "upper bound \"1/x\" at byte 4 is not a number"
+        );
+    }
+
+    #[test]
+    fn test_brackets() {
+        let pos = ival("0, 5").with_lo_open(true);
+        assert_eq!(pos.to_string(), "(0, 5]");
+        assert_eq!(ival("(0, 5]"), pos);
+        assert_eq!(ival(" [ 0 , 5 ] "), ival("0, 5"));
+        assert_eq!(ival("[0, inf]"), ival("[0, inf)"));
+        assert_eq!(ival("(1, 1]"), Interval::empty());
+
+        // whatever gets printed can be read back in
+        for i in [
+            ival("(-inf, 5]"),
+            ival("[-1/3, 1/3)"),
+            ival("(0.25, inf)"),
+            Interval::default(),
+            Interval::empty(),
+            Interval::singleton(BigInt::from(7)),
+            ival("(0, 1)") * ival("[-2, 3]"),
+            ival("1, inf").recip(),
+        ] {
+            assert_eq!(ival(&i.to_string()), i, "{} didn't round trip", i);
+        }
+
+        // floats too, even ones that print shorter than they are
+        for i in [
+            Interval::<f64>::from_f64s(0.1, 0.3),
+            Interval::<f64>::ival("(-1/3, 2.5]"),
+            Interval::<f64>::from_f64s(-1e10, 1e-10),
+        ] {
+            let printed = i.to_string();
+            assert_eq!(
+                Interval::<f64>::ival(&printed),
+                i,
+                "{} didn't round trip",
+                i
+            );
+        }
+        assert_eq!(Interval::<f64>::from_f64s(0.5, 2.0).to_string(), "[0.5, 2]");
+
+        use ParseIntervalError::*;
+        let err = |s: &str| s.parse::<Interval>().unwrap_err();
+        assert_eq!(err(" [0, 1"), Unclosed { offset: 1 });
+        assert_eq!(err("0, 1)"), Unopened { offset: 4 });
+        assert_eq!(err("["), Unclosed { offset: 0 });
+        assert_eq!(err("[0 1]"), MissingComma { offset: 1 });
+        assert_eq!(
+            err("[0, 1x]"),
+            InvalidNumber {
+                side: Side::Hi,
+                offset: 4,
+                text: "1x".into()
+            }
+        );
+    }
+
+    #[test]
+    fn test_lattice() {
+        let (a, b) = (ival("[0, 1)"), ival("(2, 3]"));
+        assert_eq!(a.hull(&b), ival("[0, 3]"));
+        assert_eq!(a.join(&Interval::bottom()), a);
+        assert_eq!(a.join(&ival("(-inf, 0)")), ival("(-inf, 1)"));
+        assert_eq!(a.join(&ival("(0, 1]")), ival("[0, 1]"));
+        assert_eq!(a.meet(&b), Interval::bottom());
+        assert_eq!(a.join(&Interval::top()), Interval::top());
+
+        assert!(a.leq(&ival("[0, 1]")));
+        assert!(!ival("[0, 1]").leq(&a));
+        assert!(ival("(0, 1)").leq(&a));
+        assert!(a.leq(&ival("[-1, inf)")));
+        assert!(!ival("(-inf, 0]").leq(&ival("[-1, inf)")));
+        assert!(<Interval>::bottom().leq(&Interval::bottom()));
+        assert!(!a.leq(&Interval::bottom()));
+        assert!(a.leq(&Interval::top()));
+
+        // meet and join are the greatest lower and least upper bounds
+        for (x, y) in [(&a, &b), (&a, &ival("[1/2, 5]")), (&b, &ival("-inf, 3"))] {
+            assert!(x.meet(y).leq(x) && x.meet(y).leq(y));
+            assert!(x.leq(&x.join(y)) && y.leq(&x.join(y)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "bad interval")]
+    fn test_ival_panics() {
+        ival("1/0, 2");
+    }
+
+    #[test]
+    fn test_powers() {
+        assert_eq!(-ival("[-1, 2)"), ival("(-2, 1]"));
+        assert_eq!(-ival("(-inf, 2]"), ival("[-2, inf)"));
+        assert_eq!(-<Interval>::empty(), Interval::empty());
+
+        assert_eq!(ival("[-3, 2)").abs(), ival("[0, 3]"));
+        assert_eq!(ival("(-3, -1]").abs(), ival("[1, 3)"));
+        assert_eq!(ival("(-1, 3)").abs(), ival("[0, 3)"));
+        assert_eq!(ival("(-inf, 1]").abs(), ival("[0, inf)"));
+
+        // unlike naive multiplication, squaring knows both sides are the same
+        let x = ival("[-2, 2]");
+        assert_eq!(&x * &x, ival("[-4, 4]"));
+        assert_eq!(x.square(), ival("[0, 4]"));
+        assert_eq!(ival("(1, 2]").square(), ival("(1, 4]"));
+        assert_eq!(ival("[-3, -1/2]").square(), ival("[1/4, 9]"));
+
+        assert_eq!(x.powi(3), ival("[-8, 8]"));
+        assert_eq!(ival("[-2, 1]").powi(3), ival("[-8, 1]"));
+        assert_eq!(ival("[-3, 2]").powi(4), ival("[0, 81]"));
+        assert_eq!(ival("(-inf, -1]").powi(2), ival("[1, inf)"));
+        assert_eq!(x.powi(0), ival("[1, 1]"));
+        assert_eq!(ival("[2, 4]").powi(-1), ival("[1/4, 1/2]"));
+        assert_eq!(ival("[-2, -1]").powi(-2), ival("[1/4, 1]"));
+        assert_eq!(x.powi(-2), Interval::default());
+        assert_eq!(<Interval>::empty().powi(0), Interval::empty());
+        for open_or_unbounded in ["(0, 1]", "[-2, -1)", "[0, inf)", "-inf, inf"] {
+            assert_eq!(ival(open_or_unbounded).powi(0), ival("[1, 1]"));
+        }
+
+        // huge powers don't take forever
+        assert_eq!(ival("[-1, 1]").powi(i32::MAX), ival("[-1, 1]"));
+        assert_eq!(ival("(0, 1]").powi(i32::MIN), ival("[1, inf)"));
+        assert_eq!(ival("[3/2, 3/2]").powi(7), ival("[2187/128, 2187/128]"));
+        // and rounding up keeps a tiny positive power above zero
+        let floats = Interval::<f64>::ival("[-2, 1/2]").powi(i32::MAX);
+        assert_eq!(floats, Interval::new(None, Some(f64::from_bits(1))));
+    }
+
+    // Checks a contractor against every pair of sample points it should keep.
+    fn check_contractor(
+        op: impl Fn(&Rational, &Rational) -> Option<Rational>,
+        contract: impl Fn(&Interval, &Interval, &Interval) -> (Interval, Interval),
+    ) {
+        let samples: Vec<Rational> = (-8..=8).map(|n| Rational::new(n, 2)).collect();
+        let ranges = [
+            "[-2, 2]",
+            "(0, 3]",
+            "[-4, -1)",
+            "[1, 1]",
+            "[-1, 0]",
+            "(-inf, 1]",
+            "[0, 0]",
+        ]
+        .map(ival);
+        for (a, b, c) in triples(&ranges) {
+            let (a2, b2) = contract(c, a, b);
+            for x in samples.iter().filter(|x| a.contains(x)) {
+                for y in samples.iter().filter(|y| b.contains(y)) {
+                    if op(x, y).is_some_and(|z| c.contains(&z)) {
+                        assert!(
+                            a2.contains(x) && b2.contains(y),
+                            "lost {} and {} in {} {} {}",
+                            x,
+                            y,
+                            a,
+                            b,
+                            c
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn triples(ranges: &[Interval]) -> impl Iterator<Item = (&Interval, &Interval, &Interval)> {
+        ranges.iter().flat_map(move |a| {
+            ranges
+                .iter()
+                .flat_map(move |b| ranges.iter().map(move |c| (a, b, c)))
+        })
+    }
+
+    #[test]
+    fn test_contractors() {
+        check_contractor(|x, y| Some(x + y), |c, a, b| c.contract_add(a, b));
+        check_contractor(|x, y| Some(x - y), |c, a, b| c.contract_sub(a, b));
+        check_contractor(|x, y| Some(x * y), |c, a, b| c.contract_mul(a, b));
+        check_contractor(
+            |x, y| (!y.is_zero()).then(|| x / y),
+            |c, a, b| c.contract_div(a, b),
+        );
+        check_contractor(
+            |x, _| (!x.is_zero()).then(|| x.recip()),
+            |c, a, b| (c.contract_recip(a), b.clone()),
+        );
+
+        // and they actually shrink things
+        let (a, b) = ival("[0, 1]").contract_add(&ival("[0, 10]"), &ival("[0, 10]"));
+        assert_eq!((a, b), (ival("[0, 1]"), ival("[0, 1]")));
+        let (a, b) = ival("[4, 6]").contract_sub(&ival("[0, 10]"), &ival("[1, 2]"));
+        assert_eq!((a, b), (ival("[5, 8]"), ival("[1, 2]")));
+        let (a, b) = ival("[2, 4]").contract_mul(&ival("[1, 10]"), &ival("[1, 2]"));
+        assert_eq!((a, b), (ival("[1, 4]"), ival("[1, 2]")));
+        let (a, b) = ival("[1, 2]").contract_div(&ival("[0, 10]"), &ival("[2, 3]"));
+        assert_eq!((a, b), (ival("[2, 6]"), ival("[2, 3]")));
+        assert_eq!(
+            ival("[1/4, 1/2]").contract_recip(&ival("(0, inf)")),
+            ival("[2, 4]")
+        );
+
+        // and notice when nothing fits
+        let (a, _) = ival("[5, 6]").contract_add(&ival("[0, 1]"), &ival("[0, 1]"));
+        assert!(a.is_empty());
+    }
+
+    #[test]
+    fn test_widening() {
+        let x = ival("[0, 1]");
+        assert_eq!(x.widen(&ival("[0, 1/2]")), x);
+        assert_eq!(x.widen(&ival("[0, 2]")), ival("[0, inf)"));
+        assert_eq!(x.widen(&ival("(-1, 1)")), ival("(-inf, 1]"));
+        assert_eq!(x.widen(&Interval::empty()), x);
+        assert_eq!(Interval::empty().widen(&x), x);
+        assert_eq!(ival("(0, 1]").widen(&ival("[0, 1]")), ival("(-inf, 1]"));
+
+        let thresholds = [-1, 0, 10, 100].map(Rational::from);
+        let widen = |a: &str, b: &str| ival(a).widen_with_thresholds(&ival(b), &thresholds);
+        assert_eq!(widen("[0, 1]", "[0, 2]"), ival("[0, 10]"));
+        assert_eq!(widen("[0, 10]", "[0, 11]"), ival("[0, 100]"));
+        assert_eq!(widen("[0, 100]", "[0, 101]"), ival("[0, inf)"));
+        assert_eq!(widen("[0, 1]", "(-1/2, 1]"), ival("[-1, 1]"));
+        assert_eq!(widen("[0, 1]", "[-5, 1]"), ival("(-inf, 1]"));
+
+        let wide = ival("[0, inf)");
+        assert_eq!(wide.narrow(&ival("[1, 2]")), ival("[0, 2]"));
+        assert_eq!(ival("(-inf, inf)").narrow(&ival("(0, 2)")), ival("(0, 2)"));
+        assert_eq!(wide.narrow(&Interval::empty()), Interval::empty());
+    }
+
+    #[test]
+    fn test_comparisons() {
+        assert!(ival("(0, 1)").is_subset_of(&ival("[0, 1]")));
+        assert!(!ival("[0, 1]").is_subset_of(&ival("(0, 1]")));
+        assert!(ival("[0, 1]").overlaps(&ival("[1, 2]")));
+        assert!(ival("[0, 1)").is_disjoint(&ival("[1, 2]")));
+        assert!(ival("empty").is_disjoint(&Interval::default()));
+
+        use Certainty::*;
+        let lt = |a: &str, b: &str| ival(a).cmp_lt(&ival(b));
+        assert_eq!(lt("[0, 1]", "(1, 2]"), True);
+        assert_eq!(lt("[0, 1)", "[1, 2]"), True);
+        assert_eq!(lt("[0, 1]", "[1, 2]"), Unknown);
+        assert_eq!(lt("[1, 2]", "[0, 1]"), False);
+        assert_eq!(lt("(-inf, 0]", "[1, inf)"), True);
+        assert_eq!(lt("[1, inf)", "(-inf, 1]"), False);
+        assert_eq!(lt("(-inf, inf)", "[0, 0]"), Unknown);
+
+        let le = |a: &str, b: &str| ival(a).cmp_le(&ival(b));
+        assert_eq!(le("[0, 1]", "[1, 2]"), True);
+        assert_eq!(le("[1, 2]", "[0, 1]"), Unknown);
+        assert_eq!(le("(1, 2]", "[0, 1]"), False);
+        assert_eq!(le("[0, 0]", "[0, 0]"), True);
+
+        let eq = |a: &str, b: &str| ival(a).cmp_eq(&ival(b));
+        assert_eq!(eq("[2, 2]", "[2, 2]"), True);
+        assert_eq!(eq("[0, 1]", "[0, 1]"), Unknown);
+        assert_eq!(eq("[0, 1)", "[1, 2]"), False);
+        assert_eq!(eq("empty", "[1, 1]"), Unknown);
+        assert!(eq("[0, 1]", "(1, 2)").is_false());
+        assert!(lt("[0, 1]", "(1, 2)").is_true());
+    }
+
+    #[test]
+    fn test_simplify() {
+        // small bounds are left alone
+        assert_eq!(ival("(1/3, 2/3]").simplify(8), ival("(1/3, 2/3]"));
+        assert_eq!(ival("[-5, inf)").simplify(8), ival("[-5, inf)"));
+
+        let i = ival("[123456789/987654321, 987654321/123456789)");
+        let simple = i.simplify(8);
+        assert!(i.is_subset_of(&simple));
+        assert_eq!(simple, ival("[255/2048, 257/32)"));
+
+        // floats are already cheap
+        let f = Interval::<f64>::ival("0.1, 0.2");
+        assert_eq!(f.simplify(8), f);
+    }
+
+    #[test]
+    fn test_metrics() {
+        let r = |s: &str| ival(s).get_constant().cloned();
+        let x = ival("[-3, 1)");
+        assert_eq!(x.width(), r("4, 4"));
+        assert_eq!(x.midpoint(), r("-1, -1"));
+        assert_eq!(x.radius(), r("2, 2"));
+        assert_eq!(x.mag(), r("3, 3"));
+        assert_eq!(x.mig(), r("0, 0"));
+        assert_eq!(ival("(-5, -1/2]").mig(), r("1/2, 1/2"));
+        assert_eq!(ival("(2, inf)").mig(), r("2, 2"));
+        assert_eq!(ival("(2, inf)").width(), None);
+        assert_eq!(ival("(2, inf)").mag(), None);
+        assert_eq!(<Interval>::empty().midpoint(), None);
+        assert_eq!(
+            Interval::from_f64s(f64::MAX, f64::MAX).midpoint(),
+            Some(f64::MAX)
+        );
+
+        // narrower is smaller, and every finite width beats an infinite one
+        let spread = |s: &str| ival(s).spread();
+        assert_eq!(spread("[0, 1/2]"), Spread::Finite(r("1/2, 1/2").unwrap()));
+        assert!(spread("empty") < spread("[0, 0]"));
+        assert!(spread("[0, 1]") < spread("[5, 7)"));
+        assert!(spread("[0, 1000]") < spread("[0, inf)"));
+        assert!(spread("[0, inf)") < spread("(-inf, inf)"));
+        assert_eq!(spread("(-inf, 0]"), spread("[0, inf)"));
+        assert_eq!(spread("[1, 3]").to_string(), "2");
+    }
+
+    #[test]
+    fn test_f64_bounds() {
+        let contains = |i: &Interval<f64>, n: BigRational| {
+            i.lo.is_none_or(|lo| lo.to_rational() <= n)
+                && i.hi.is_none_or(|hi| n <= hi.to_rational())
+        };
+        let rat = |s: &str| ival(s).get_constant().unwrap().to_rational();
+
+        // 1/3 isn't a float, so it gets a tiny interval around it
+        let third = Interval::<f64>::ival("1/3, 1/3");
+        assert!(third.get_constant().is_none());
+        assert!(contains(&third, rat("1/3, 1/3")));
+        assert_eq!(third, Interval::from_rational(&rat("1/3, 1/3")));
+
+        // but anything that is a float stays exact
+        let x = Interval::<f64>::ival("[-1, 0.5)");
+        assert_eq!(x, Interval::from_f64s(-1.0, 0.5).with_hi_open(true));
+        assert_eq!(x.to_string(), "[-1, 0.5)");
+        assert_eq!(x.square(), Interval::<f64>::ival("[0, 1]"));
+        assert_eq!(
+            Interval::<f64>::ival("1, inf").recip(),
+            Interval::ival("(0, 1]")
+        );
+
+        // rounding errors always widen the result
+        let tenth = Interval::<f64>::singleton(0.1);
+        let sum = &tenth + &Interval::singleton(0.2);
+        assert!(contains(
+            &sum,
+            BigRational::from_float(0.1).unwrap() + BigRational::from_float(0.2).unwrap()
+        ));
+        assert!(sum.lo.unwrap() < sum.hi.unwrap());
+        let quotient = Interval::<f64>::singleton(1.0) / Interval::singleton(3.0);
+        assert!(contains(&quotient, rat("1/3, 1/3")));
+        assert!(contains(&(&third * &third * &third), rat("1/27, 1/27")));
+        assert!(contains(&third.powi(-3), rat("27, 27")));
+
+        // and overflowing just loses the bound
+        let huge = Interval::<f64>::singleton(f64::MAX);
+        assert_eq!(&huge + &huge, Interval::new(Some(f64::MAX), None));
+        assert_eq!(-&huge * &huge, Interval::new(None, Some(-f64::MAX)));
+    }
+
     #[test]
     fn test_math() {}
 }
@@ -0,0 +1,297 @@
+use crate::{ival, Interval, Lattice, Rational};
+use auto_ops::*;
+use std::cmp::Ordering;
+
+/// A union of disjoint intervals, kept sorted.
+///
+/// This is more precise than a single [`Interval`] when a range has holes,
+/// like the result of dividing by something that might be zero.
+/// Each set has a maximum number of pieces; if an operation would produce
+/// more than that, the set falls back to the hull of all of them.
+/// Two sets are equal when they have the same pieces, whatever their limits.
+#[derive(Debug, Clone)]
+pub struct IntervalSet {
+    // sorted, non-empty, and with gaps between them
+    pieces: Vec<Interval>,
+    max_pieces: usize,
+}
+
+impl PartialEq for IntervalSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.pieces == other.pieces
+    }
+}
+
+impl Eq for IntervalSet {}
+
+impl std::hash::Hash for IntervalSet {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.pieces.hash(state)
+    }
+}
+
+impl Default for IntervalSet {
+    fn default() -> Self {
+        Interval::default().into()
+    }
+}
+
+impl From<Interval> for IntervalSet {
+    fn from(interval: Interval) -> Self {
+        Self::from_pieces([interval])
+    }
+}
+
+impl From<IntervalSet> for Interval {
+    fn from(set: IntervalSet) -> Self {
+        set.hull()
+    }
+}
+
+impl std::fmt::Display for IntervalSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return write!(f, "empty");
+        }
+        for (i, piece) in self.pieces.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ∪ ")?;
+            }
+            piece.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+// Orders intervals by where they start.
+fn cmp_lo(a: &Interval, b: &Interval) -> Ordering {
+    match (a.lo(), b.lo()) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Less,
+        (Some(_), None) => Ordering::Greater,
+        // closed bounds start just before open ones
+        (Some(x), Some(y)) => x.cmp(y).then(a.lo_open().cmp(&b.lo_open())),
+    }
+}
+
+// Whether `b`, which starts no earlier than `a`, overlaps or touches it,
+// so their union has no gap in it.
+fn connected(a: &Interval, b: &Interval) -> bool {
+    match (a.hi(), b.lo()) {
+        (None, _) | (_, None) => true,
+        (Some(hi), Some(lo)) => hi > lo || (hi == lo && !(a.hi_open() && b.lo_open())),
+    }
+}
+
+impl IntervalSet {
+    /// The default for [`IntervalSet::max_pieces`].
+    pub const DEFAULT_MAX_PIECES: usize = 8;
+
+    /// The union of the given intervals.
+    pub fn from_pieces(pieces: impl IntoIterator<Item = Interval>) -> Self {
+        Self::with_limit(pieces, Self::DEFAULT_MAX_PIECES)
+    }
+
+    /// The set containing nothing.
+    pub fn empty() -> Self {
+        Self::from_pieces([])
+    }
+
+    /// Sets the maximum number of pieces, falling back to the hull
+    /// right away if there are already too many.
+    pub fn with_max_pieces(self, max_pieces: usize) -> Self {
+        Self::with_limit(self.pieces, max_pieces)
+    }
+
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    // Sorts the pieces and glues together any that overlap.
+    fn with_limit(pieces: impl IntoIterator<Item = Interval>, max_pieces: usize) -> Self {
+        let mut sorted: Vec<Interval> = pieces.into_iter().filter(|i| !i.is_empty()).collect();
+        sorted.sort_by(cmp_lo);
+
+        let mut pieces: Vec<Interval> = Vec::with_capacity(sorted.len());
+        for piece in sorted {
+            match pieces.last_mut() {
+                Some(last) if connected(last, &piece) => *last = last.hull(&piece),
+                _ => pieces.push(piece),
+            }
+        }
+
+        let max_pieces = max_pieces.max(1);
+        if pieces.len() > max_pieces {
+            let hull = pieces.iter().fold(Interval::empty(), |acc, i| acc.hull(i));
+            pieces = vec![hull];
+        }
+        Self { pieces, max_pieces }
+    }
+
+    /// The disjoint intervals making up this set, in increasing order.
+    pub fn pieces(&self) -> &[Interval] {
+        &self.pieces
+    }
+
+    /// The smallest single interval containing the whole set.
+    pub fn hull(&self) -> Interval {
+        match (self.pieces.first(), self.pieces.last()) {
+            (Some(first), Some(last)) => first.hull(last),
+            _ => Interval::empty(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pieces.is_empty()
+    }
+
+    pub fn get_constant(&self) -> Option<&Rational> {
+        match self.pieces.as_slice() {
+            [piece] => piece.get_constant(),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, n: &Rational) -> bool {
+        self.pieces.iter().any(|piece| piece.contains(n))
+    }
+
+    pub fn contains_zero(&self) -> bool {
+        self.pieces.iter().any(|piece| piece.contains_zero())
+    }
+
+    /// The numbers in both sets.
+    pub fn intersect(&self, other: &Self) -> Self {
+        self.pairwise(other, |a, b| a.intersect(b))
+    }
+
+    /// The numbers in either set.
+    pub fn union(&self, other: &Self) -> Self {
+        let pieces = self.pieces.iter().chain(&other.pieces).cloned();
+        Self::with_limit(pieces, self.max_pieces.min(other.max_pieces))
+    }
+
+    /// Unlike [`Interval::recip`], zero is just dropped from the divisor,
+    /// since dividing by it is undefined anyway.
+    /// So `1 / [-1, 2]` is `(-inf, -1] ∪ [1/2, inf)`.
+    /// Only a set containing nothing but zero gives back the whole line.
+    pub fn recip(&self) -> Self {
+        let negative = ival("(-inf, 0)");
+        let positive = ival("(0, inf)");
+        let pieces: Vec<Interval> = self
+            .pieces
+            .iter()
+            .flat_map(|piece| [piece.intersect(&negative), piece.intersect(&positive)])
+            .filter(|piece| !piece.is_empty())
+            .map(|piece| piece.recip())
+            .collect();
+        if pieces.is_empty() && !self.is_empty() {
+            return Self::default().with_max_pieces(self.max_pieces);
+        }
+        Self::with_limit(pieces, self.max_pieces)
+    }
+
+    // Applies `f` to every pair of pieces, collecting the results.
+    fn pairwise(&self, other: &Self, f: impl Fn(&Interval, &Interval) -> Interval) -> Self {
+        let pieces = self
+            .pieces
+            .iter()
+            .flat_map(|a| other.pieces.iter().map(|b| f(a, b)).collect::<Vec<_>>());
+        Self::with_limit(pieces, self.max_pieces.min(other.max_pieces))
+    }
+}
+
+impl Lattice for IntervalSet {
+    fn top() -> Self {
+        Self::default()
+    }
+
+    fn bottom() -> Self {
+        Self::empty()
+    }
+
+    fn meet(&self, other: &Self) -> Self {
+        self.intersect(other)
+    }
+
+    fn join(&self, other: &Self) -> Self {
+        self.union(other)
+    }
+
+    fn leq(&self, other: &Self) -> bool {
+        self.pieces
+            .iter()
+            .all(|a| other.pieces.iter().any(|b| a.leq(b)))
+    }
+}
+
+impl_op_ex!(+ |a: &IntervalSet, b: &IntervalSet| -> IntervalSet {
+    a.pairwise(b, |a, b| a + b)
+});
+
+impl_op_ex!(-|a: &IntervalSet, b: &IntervalSet| -> IntervalSet { a.pairwise(b, |a, b| a - b) });
+
+impl_op_ex!(*|a: &IntervalSet, b: &IntervalSet| -> IntervalSet { a.pairwise(b, |a, b| a * b) });
+
+impl_op_ex!(/ |a: &IntervalSet, b: &IntervalSet| -> IntervalSet {
+    a * b.recip()
+});
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iset(pieces: &[&str]) -> IntervalSet {
+        IntervalSet::from_pieces(pieces.iter().map(|s| ival(s)))
+    }
+
+    #[test]
+    fn test_normalize() {
+        let set = iset(&["[4, 5]", "[0, 1)", "[1, 2]", "(6, 7)", "empty", "[7, 8]"]);
+        assert_eq!(set, iset(&["[0, 2]", "[4, 5]", "(6, 8]"]));
+        assert_eq!(set.to_string(), "[0, 2] ∪ [4, 5] ∪ (6, 8]");
+        assert_eq!(set.hull(), ival("[0, 8]"));
+
+        // a point missing between two open ends is a real gap
+        assert_eq!(iset(&["(0, 1)", "(1, 2)"]).pieces().len(), 2);
+
+        // too many pieces falls back to the hull
+        let capped = set.clone().with_max_pieces(2);
+        assert_eq!(capped, iset(&["[0, 8]"]));
+
+        // the limit isn't part of the value
+        assert_eq!(set.clone().with_max_pieces(100), set);
+    }
+
+    #[test]
+    fn test_division_across_zero() {
+        let one = IntervalSet::from(ival("[1, 1]"));
+        let divisor = IntervalSet::from(ival("[-1, 2]"));
+        assert_eq!(&one / &divisor, iset(&["(-inf, -1]", "[1/2, inf)"]));
+        assert_eq!(Interval::from(&one / &divisor), Interval::default());
+
+        assert_eq!(
+            IntervalSet::from(ival("[0, 0]")).recip(),
+            IntervalSet::default()
+        );
+        assert_eq!(
+            IntervalSet::from(ival("[0, 2]")).recip(),
+            iset(&["[1/2, inf)"])
+        );
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = iset(&["[-2, -1]", "[1, 2]"]);
+        assert_eq!(&a + &iset(&["[10, 10]"]), iset(&["[8, 9]", "[11, 12]"]));
+        assert_eq!(&a - &a, iset(&["[-4, -2]", "[-1, 1]", "[2, 4]"]));
+        assert_eq!(&a * &a, iset(&["[-4, -1]", "[1, 4]"]));
+        assert_eq!(&iset(&["[1, 1]"]) / &a, iset(&["[-1, -1/2]", "[1/2, 1]"]));
+
+        assert!(!a.contains_zero());
+        assert_eq!(a.intersect(&iset(&["[0, 3]"])), iset(&["[1, 2]"]));
+        assert!(a.leq(&iset(&["[-2, 2]"])));
+        assert!(!iset(&["[-2, 2]"]).leq(&a));
+        assert_eq!(a.join(&iset(&["[-1, 1]"])), iset(&["[-2, 2]"]));
+    }
+}
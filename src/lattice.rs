@@ -0,0 +1,111 @@
+use egg::DidMerge;
+
+/// A lattice of abstract values, ordered by how much they allow.
+///
+/// [`top`](Lattice::top) knows nothing about a value,
+/// and [`bottom`](Lattice::bottom) is impossible.
+/// Abstract domains like [`Interval`](crate::Interval) implement this
+/// so analyses can be written once for all of them.
+pub trait Lattice: Sized {
+    /// The element that allows anything.
+    fn top() -> Self;
+
+    /// The element that allows nothing.
+    fn bottom() -> Self;
+
+    /// The greatest lower bound, allowing only what both allow.
+    fn meet(&self, other: &Self) -> Self;
+
+    /// The least upper bound, allowing what either allows.
+    fn join(&self, other: &Self) -> Self;
+
+    /// Whether `self` allows no more than `other`.
+    fn leq(&self, other: &Self) -> bool;
+
+    fn is_top(&self) -> bool {
+        Self::top().leq(self)
+    }
+
+    fn is_bottom(&self) -> bool {
+        self.leq(&Self::bottom())
+    }
+}
+
+/// Merges `from` into `to` by taking their meet,
+/// for use in [`egg::Analysis::merge`].
+/// The returned [`DidMerge`] is precise: each side is
+/// only marked as changed if the meet is strictly below it.
+pub fn merge_meet<L: Lattice>(to: &mut L, from: L) -> DidMerge {
+    let merged = to.meet(&from);
+    let did_merge = DidMerge(!to.leq(&merged), !from.leq(&merged));
+    *to = merged;
+    did_merge
+}
+
+/// A lattice with infinite ascending chains, like intervals over the rationals,
+/// and a way to jump past them so fixpoint iteration terminates.
+pub trait Widen: Lattice {
+    /// An upper bound of both `self` and `next` that only ever climbs
+    /// finitely many times in a row.
+    fn widen(&self, next: &Self) -> Self;
+
+    /// Something between `next` and `self` (usually the result of widening),
+    /// winning back some of the precision widening gave up.
+    /// Like widening, it can only be applied finitely many times in a row before it stops changing.
+    fn narrow(&self, next: &Self) -> Self;
+}
+
+/// Finds a post-fixpoint of a monotone `f` starting from `start`:
+/// some `x` above `start` with `f(x)` below `x`.
+///
+/// First this climbs with [`Widen::widen`] until `f` stops growing,
+/// then takes up to `narrowing_steps` steps down with [`Widen::narrow`].
+pub fn fixpoint<L: Widen>(start: L, f: impl Fn(&L) -> L, narrowing_steps: usize) -> L {
+    let mut x = start;
+    loop {
+        let next = x.join(&f(&x));
+        if next.leq(&x) {
+            break;
+        }
+        x = x.widen(&next);
+    }
+    for _ in 0..narrowing_steps {
+        let next = x.narrow(&f(&x));
+        if x.leq(&next) {
+            break;
+        }
+        x = next;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ival, Interval};
+
+    #[test]
+    fn test_merge_meet() {
+        let mut to = ival("[0, 2]");
+        let DidMerge(changed_to, changed_from) = merge_meet(&mut to, ival("[1, 3]"));
+        assert_eq!(to, ival("[1, 2]"));
+        assert!(changed_to && changed_from);
+
+        let DidMerge(changed_to, changed_from) = merge_meet(&mut to, ival("(-inf, 5]"));
+        assert_eq!(to, ival("[1, 2]"));
+        assert!(!changed_to && changed_from);
+    }
+
+    #[test]
+    fn test_fixpoint() {
+        // i = 0; while i < 10 { i += 1 } climbs one step at a time without widening
+        let one = ival("1, 1");
+        let step = |i: &Interval| ival("0, 0").hull(&(i + &one).intersect(&ival("-inf, 10")));
+        assert_eq!(fixpoint(ival("0, 0"), step, 0), ival("[0, inf)"));
+        assert_eq!(fixpoint(ival("0, 0"), step, 1), ival("[0, 10]"));
+
+        // x = x + 1 really does grow forever, so narrowing can't help
+        let count = |x: &Interval| x + &one;
+        assert_eq!(fixpoint(ival("0, 0"), count, 5), ival("[0, inf)"));
+    }
+}
@@ -34,6 +34,51 @@
 
 // The provided, simple interval library
 mod interval;
+// The numbers intervals are bounded by, and how they round
+mod bound;
+// Exact rational bounds that stay in machine integers while they're small
+mod rational;
+// Unions of intervals, for when a range has holes in it
+mod interval_set;
+// Ranges of machine integers, which wrap around when they overflow
+mod int_interval;
+// sqrt, exp, ln and friends on intervals
+mod transcendental;
+// A trait for abstract domains like intervals
+mod lattice;
+// Splitting ranges up to evaluate them piece by piece
+mod subdivide;
+// Affine forms, which remember how ranges depend on each other
+mod affine;
+// Printing intervals as rounded decimals
+mod display;
+// The rational arithmetic language from the tutorial
+mod math;
+// Constant folding, interval and affine analyses for `Math`
+mod analysis;
+// Rewrite rules for `Math`
+mod rules;
+// Evaluating `Math` expressions on plain numbers
+mod eval;
+// Parsing `Math` expressions like `1 - 2*y/(x + y)`
+mod infix;
+// Saving and loading intervals, with the `serde` feature
+#[cfg(feature = "serde")]
+mod serialize;
 
 // re-export all the public items
+pub use affine::*;
+pub use analysis::*;
+pub use bound::*;
+pub use display::*;
+pub use eval::*;
+pub use infix::*;
+pub use int_interval::*;
 pub use interval::*;
+pub use interval_set::*;
+pub use lattice::*;
+pub use math::*;
+pub use rational::*;
+pub use rules::*;
+pub use subdivide::*;
+pub use transcendental::*;
@@ -0,0 +1,25 @@
+use egg::{define_language, Id, Symbol};
+use num::BigRational;
+
+// E-nodes implement the `egg::Language` trait, and they are typically some
+// pairing of a operator and a list of children (as `Id`s).
+//
+// The `define_language` macro makes it easy for you to make your own
+// `Language`s from a Rust enum. This implements some nice parsing for you as well.
+define_language! {
+    /// The rational arithmetic language from the tutorial, like `(- 1 (/ (* 2 y) (+ x y)))`.
+    ///
+    /// Numbers parse as exact rationals and anything else that isn't an operator is a variable.
+    pub enum Math {
+        // This parses rationals as enums, like "4"
+        Num(BigRational),
+        // This will parse a "+" symbol as an Add with two children
+        // Note that the value of this variant is a [Id; 2], or an array of 2 Id's.
+        "+" = Add([Id; 2]),
+        "-" = Sub([Id; 2]),
+        "*" = Mul([Id; 2]),
+        "/" = Div([Id; 2]),
+        // Finally, this will parse anything else as a Var, like "foo"
+        Var(Symbol),
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{Bound, Round};
+use auto_ops::*;
+use num::rational::Ratio;
+use num::{
+    BigInt, BigRational, CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, Num, One, Signed,
+    ToPrimitive, Zero,
+};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+
+/// An exact rational number like [`BigRational`],
+/// but kept in `i64`s while it fits, only moving to big integers when it has to.
+///
+/// This is the default bound for [`Interval`](crate::Interval).
+/// Most bounds in practice are small, like 0, -1 or 1/2,
+/// and those never allocate this way.
+/// Use `From` to convert to and from [`BigRational`].
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Rational(Repr);
+
+// Each number has just one representation, so the derived PartialEq and Hash work:
+// it's small whenever it fits, and either way it's in lowest terms.
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Repr {
+    // never has i64::MIN as the numerator, since that can't be negated
+    Small(Ratio<i64>),
+    Big(BigRational),
+}
+
+impl Rational {
+    fn from_small(n: Ratio<i64>) -> Self {
+        if *n.numer() == i64::MIN {
+            Self(Repr::Big(to_big(&n)))
+        } else {
+            Self(Repr::Small(n))
+        }
+    }
+
+    /// The fraction `numer / denom`.
+    /// Panics if `denom` is zero.
+    pub fn new(numer: i64, denom: i64) -> Self {
+        Self::from(BigRational::new(numer.into(), denom.into()))
+    }
+
+    /// Whether this is held in machine integers instead of big ones.
+    pub fn is_small(&self) -> bool {
+        matches!(self.0, Repr::Small(_))
+    }
+
+    /// One over the number. Panics if it's zero.
+    pub fn recip(&self) -> Self {
+        match &self.0 {
+            Repr::Small(n) => Self::from_small(n.recip()),
+            Repr::Big(n) => Self::from(n.recip()),
+        }
+    }
+
+    fn as_big(&self) -> Cow<'_, BigRational> {
+        match &self.0 {
+            Repr::Small(n) => Cow::Owned(to_big(n)),
+            Repr::Big(n) => Cow::Borrowed(n),
+        }
+    }
+}
+
+fn to_big(n: &Ratio<i64>) -> BigRational {
+    BigRational::new_raw(BigInt::from(*n.numer()), BigInt::from(*n.denom()))
+}
+
+// Runs `small` on machine integers if both sides fit and nothing overflows,
+// and `big` on big integers otherwise. Either way the result is the same.
+fn op(
+    a: &Rational,
+    b: &Rational,
+    small: impl FnOnce(&Ratio<i64>, &Ratio<i64>) -> Option<Ratio<i64>>,
+    big: impl FnOnce(&BigRational, &BigRational) -> BigRational,
+) -> Rational {
+    if let (Repr::Small(x), Repr::Small(y)) = (&a.0, &b.0) {
+        if let Some(r) = small(x, y) {
+            return Rational::from_small(r);
+        }
+    }
+    Rational::from(big(&a.as_big(), &b.as_big()))
+}
+
+impl From<BigRational> for Rational {
+    fn from(n: BigRational) -> Self {
+        let small = n
+            .numer()
+            .to_i64()
+            .filter(|n| *n != i64::MIN)
+            .zip(n.denom().to_i64());
+        match small {
+            Some((numer, denom)) => Self(Repr::Small(Ratio::new_raw(numer, denom))),
+            None => Self(Repr::Big(n)),
+        }
+    }
+}
+
+impl From<BigInt> for Rational {
+    fn from(n: BigInt) -> Self {
+        Self::from(BigRational::from_integer(n))
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Self::from_small(Ratio::from_integer(n))
+    }
+}
+
+impl From<Rational> for BigRational {
+    fn from(n: Rational) -> Self {
+        match n.0 {
+            Repr::Small(n) => to_big(&n),
+            Repr::Big(n) => n,
+        }
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            Repr::Small(n) => fmt::Display::fmt(n, f),
+            Repr::Big(n) => fmt::Display::fmt(n, f),
+        }
+    }
+}
+
+// Just the number, since the representation is an implementation detail.
+impl fmt::Debug for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (&self.0, &other.0) {
+            (Repr::Small(a), Repr::Small(b)) => a.cmp(b),
+            _ => self.as_big().cmp(&other.as_big()),
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl_op_ex!(+ |a: &Rational, b: &Rational| -> Rational { op(a, b, Ratio::checked_add, |a, b| a + b) });
+impl_op_ex!(-|a: &Rational, b: &Rational| -> Rational {
+    op(a, b, Ratio::checked_sub, |a, b| a - b)
+});
+impl_op_ex!(*|a: &Rational, b: &Rational| -> Rational {
+    op(a, b, Ratio::checked_mul, |a, b| a * b)
+});
+impl_op_ex!(/ |a: &Rational, b: &Rational| -> Rational { op(a, b, Ratio::checked_div, |a, b| a / b) });
+impl_op_ex!(% |a: &Rational, b: &Rational| -> Rational { Rational::from(&*a.as_big() % &*b.as_big()) });
+
+impl_op_ex!(-|a: &Rational| -> Rational {
+    match &a.0 {
+        Repr::Small(n) => Rational::from_small(-n),
+        Repr::Big(n) => Rational::from(-n),
+    }
+});
+
+impl Zero for Rational {
+    fn zero() -> Self {
+        Self::from(0)
+    }
+
+    fn is_zero(&self) -> bool {
+        match &self.0 {
+            Repr::Small(n) => n.is_zero(),
+            Repr::Big(_) => false,
+        }
+    }
+}
+
+impl One for Rational {
+    fn one() -> Self {
+        Self::from(1)
+    }
+}
+
+impl Num for Rational {
+    type FromStrRadixErr = <BigRational as Num>::FromStrRadixErr;
+
+    fn from_str_radix(s: &str, radix: u32) -> Result<Self, Self::FromStrRadixErr> {
+        BigRational::from_str_radix(s, radix).map(Self::from)
+    }
+}
+
+impl Signed for Rational {
+    fn abs(&self) -> Self {
+        if self.is_negative() {
+            -self
+        } else {
+            self.clone()
+        }
+    }
+
+    fn abs_sub(&self, other: &Self) -> Self {
+        if self <= other {
+            Self::zero()
+        } else {
+            self - other
+        }
+    }
+
+    fn signum(&self) -> Self {
+        match &self.0 {
+            Repr::Small(n) => Self::from_small(n.signum()),
+            Repr::Big(n) => Self::from(n.signum()),
+        }
+    }
+
+    fn is_positive(&self) -> bool {
+        match &self.0 {
+            Repr::Small(n) => n.is_positive(),
+            Repr::Big(n) => n.is_positive(),
+        }
+    }
+
+    fn is_negative(&self) -> bool {
+        match &self.0 {
+            Repr::Small(n) => n.is_negative(),
+            Repr::Big(n) => n.is_negative(),
+        }
+    }
+}
+
+// How many bits the magnitude of `n` takes, like `BigInt::bits`.
+fn bits(n: i64) -> u32 {
+    64 - n.unsigned_abs().leading_zeros()
+}
+
+/// Exact, just like [`BigRational`], but small numbers are computed with `i64`s.
+impl Bound for Rational {
+    fn add_rounded(&self, other: &Self, _round: Round) -> Self {
+        self + other
+    }
+
+    fn sub_rounded(&self, other: &Self, _round: Round) -> Self {
+        self - other
+    }
+
+    fn mul_rounded(&self, other: &Self, _round: Round) -> Self {
+        self * other
+    }
+
+    fn div_rounded(&self, other: &Self, _round: Round) -> Self {
+        self / other
+    }
+
+    fn from_rational(n: &BigRational, _round: Round) -> Self {
+        Self::from(n.clone())
+    }
+
+    fn to_rational(&self) -> BigRational {
+        self.as_big().into_owned()
+    }
+
+    /// Rounds to about `bits` significant bits if the numerator or denominator is any bigger.
+    fn simplify(&self, bits_limit: u32, round: Round) -> Self {
+        match &self.0 {
+            Repr::Small(n) if bits(*n.numer()) <= bits_limit && bits(*n.denom()) <= bits_limit => {
+                self.clone()
+            }
+            _ => Self::from(self.to_rational().simplify(bits_limit, round)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_as_big() {
+        let q = |n: i64, d: i64| BigRational::new(n.into(), d.into());
+        let values = [
+            q(0, 1),
+            q(-1, 1),
+            q(1, 3),
+            q(-7, 2),
+            q(i64::MAX, 1),
+            q(-i64::MAX, 1),
+            q(i64::MIN, 1),
+            q(i64::MIN, 3),
+            q(1, i64::MAX),
+            q(i64::MAX, 1) * q(4, 1),
+        ];
+        for a in &values {
+            let x = Rational::from(a.clone());
+            assert_eq!(BigRational::from(x.clone()), *a);
+            assert_eq!((-&x).to_rational(), -a);
+            assert_eq!(x.abs().to_rational(), a.abs());
+            if !a.is_zero() {
+                assert_eq!(x.recip().to_rational(), a.recip());
+            }
+            for b in &values {
+                let y = Rational::from(b.clone());
+                assert_eq!((&x + &y).to_rational(), a + b);
+                assert_eq!((&x - &y).to_rational(), a - b);
+                assert_eq!((&x * &y).to_rational(), a * b);
+                if !b.is_zero() {
+                    assert_eq!((&x / &y).to_rational(), a / b);
+                }
+                assert_eq!(x.cmp(&y), a.cmp(b));
+                // one representation each, so equality is just equality
+                assert_eq!(x == y, a == b);
+            }
+        }
+    }
+
+    #[test]
+    fn test_small_and_big() {
+        let max = Rational::from(i64::MAX);
+        assert!(max.is_small());
+        assert!(Rational::new(-1, 3).is_small());
+        assert!(!Rational::from(i64::MIN).is_small());
+
+        // overflowing i64 moves to big integers, and coming back moves back
+        let sum = &max + &max;
+        assert!(!sum.is_small());
+        assert_eq!(
+            sum.to_rational(),
+            BigRational::from_integer(BigInt::from(i64::MAX) * 2)
+        );
+        assert_eq!(&sum - &max, max);
+        assert!((&sum - &max).is_small());
+        assert_eq!(&(&max * &max) / &max, max);
+
+        assert_eq!(Rational::new(2, -4).to_string(), "-1/2");
+        assert_eq!(format!("{:?}", sum), "18446744073709551614");
+        assert!(Rational::new(1, 3) > Rational::new(-1, 2));
+        assert!(sum > max && -&sum < -&max);
+    }
+
+    #[test]
+    fn test_simplify() {
+        let x = Rational::new(12345, 67891);
+        assert_eq!(x.simplify(64, Round::Down), x);
+        let (lo, hi) = (x.simplify(8, Round::Down), x.simplify(8, Round::Up));
+        assert!(lo < x && x < hi);
+        assert_eq!(lo.to_rational(), x.to_rational().simplify(8, Round::Down));
+    }
+}
@@ -0,0 +1,92 @@
+use crate::{AffineAnalysis, Bound, IntervalAnalysis, Math};
+use egg::{rewrite, Analysis, EGraph, Id, Rewrite, Subst, Var};
+
+/// Rewrites for [`Math`] that are sound no matter what the analysis is.
+///
+/// None of these cancel a division, since `x / x` is only 1 when `x` isn't zero.
+/// The division rules here are still fine with zero, because both sides
+/// always divide by the same thing.
+#[rustfmt::skip]
+pub fn math_rules<A: Analysis<Math>>() -> Vec<Rewrite<Math, A>> {
+    vec![
+        rewrite!("comm-add";  "(+ ?a ?b)"        => "(+ ?b ?a)"),
+        rewrite!("comm-mul";  "(* ?a ?b)"        => "(* ?b ?a)"),
+        rewrite!("assoc-add"; "(+ ?a (+ ?b ?c))" => "(+ (+ ?a ?b) ?c)"),
+        rewrite!("assoc-mul"; "(* ?a (* ?b ?c))" => "(* (* ?a ?b) ?c)"),
+
+        rewrite!("sub-canon"; "(- ?a ?b)" => "(+ ?a (* -1 ?b))"),
+        rewrite!("canon-sub"; "(+ ?a (* -1 ?b))" => "(- ?a ?b)"),
+        rewrite!("cancel-sub"; "(- ?a ?a)" => "0"),
+
+        rewrite!("flip-sub"; "(- ?a ?b)" => "(* -1 (- ?b ?a))"),
+
+        rewrite!("add2-mul"; "(+ ?a ?a)" => "(* 2 ?a)"),
+        rewrite!("mul-add2"; "(* 2 ?a)"  => "(+ ?a ?a)"),
+
+        rewrite!("zero-add"; "(+ ?a 0)" => "?a"),
+        rewrite!("zero-mul"; "(* ?a 0)" => "0"),
+        rewrite!("one-mul";  "(* ?a 1)" => "?a"),
+
+        rewrite!("distribute"; "(* ?a (+ ?b ?c))"        => "(+ (* ?a ?b) (* ?a ?c))"),
+        rewrite!("factor"    ; "(+ (* ?a ?b) (* ?a ?c))" => "(* ?a (+ ?b ?c))"),
+
+        // These div rules are **not** unsound, even with the possibility of dividing by zero.
+        // Note how the left and right sides are "equally" sound, i.e., you always divide by ?c
+        // You get in trouble when you make things more or less "sound" by changing what you divide by
+        rewrite!("add-to-frac"; "(+ ?a (/ ?b ?c))" => "(/ (+ (* ?a ?c) ?b) ?c)"),
+        rewrite!("frac-to-add"; "(/ (+ (* ?a ?c) ?b) ?c)" => "(+ ?a (/ ?b ?c))" ),
+        rewrite!("mul-div";     "(* ?a (/ ?b ?c))" => "(/ (* ?a ?b) ?c)"),
+        rewrite!("div-mul";     "(/ (* ?a ?b) ?c)" => "(* ?a (/ ?b ?c))"),
+        rewrite!("frac-lift";   "(/ ?a ?b)" => "(/ (- ?b (- ?b ?a)) ?b)"),
+    ]
+}
+
+/// [`math_rules`], plus the division rules that need [`IntervalAnalysis`]
+/// to know the divisor isn't zero.
+#[rustfmt::skip]
+pub fn interval_rules<B: Bound + 'static>() -> Vec<Rewrite<Math, IntervalAnalysis<B>>> {
+    let mut rules = math_rules();
+    // We can make these sound now by using the interval information!
+    // The `if` syntax allows you to add a `Condition` to a rewrite
+    rules.extend([
+        rewrite!("cancel-div"; "(/ ?a ?a)" => "1" if is_non_zero("?a")),
+        rewrite!("zero-div"; "(/ 0 ?a)" => "0" if is_non_zero("?a")),
+    ]);
+    rules
+}
+
+// The function signature output here implements the `Condition` trait in egg.
+// So by making a function that outputs such functions, we have a "factory" for easily making
+// `Condition`s to be used in rules.
+
+/// A condition for rewrites that holds when the interval for `var` doesn't contain zero.
+pub fn is_non_zero<B: Bound>(
+    var: &str,
+) -> impl Fn(&mut EGraph<Math, IntervalAnalysis<B>>, Id, &Subst) -> bool {
+    let var: Var = var.parse().unwrap();
+    move |egraph, _root, subst: &Subst| !egraph[subst[var]].data.contains_zero()
+}
+
+/// [`math_rules`], plus the same division rules as [`interval_rules`],
+/// checking the range of the [`AffineAnalysis`] form instead.
+#[rustfmt::skip]
+pub fn affine_rules() -> Vec<Rewrite<Math, AffineAnalysis>> {
+    let mut rules = math_rules();
+    rules.extend([
+        rewrite!("cancel-div"; "(/ ?a ?a)" => "1" if is_affine_non_zero("?a")),
+        rewrite!("zero-div"; "(/ 0 ?a)" => "0" if is_affine_non_zero("?a")),
+    ]);
+    rules
+}
+
+/// Like [`is_non_zero`], but for [`AffineAnalysis`].
+/// E-classes with no affine form could be anything, zero included.
+pub fn is_affine_non_zero(
+    var: &str,
+) -> impl Fn(&mut EGraph<Math, AffineAnalysis>, Id, &Subst) -> bool {
+    let var: Var = var.parse().unwrap();
+    move |egraph, _root, subst: &Subst| match &egraph[subst[var]].data {
+        Some(a) => !a.to_interval().contains_zero(),
+        None => false,
+    }
+}
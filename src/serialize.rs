@@ -0,0 +1,141 @@
+//! Serde support for [`Interval`], behind the `serde` feature.
+//!
+//! Bounds are written as exact `"num/den"` strings so nothing is lost,
+//! with `null` for infinite ones, e.g.
+//! `{"lo": "0/1", "hi": null, "lo_open": true, "hi_open": false}` for `(0, inf)`.
+//! The empty interval has null bounds and `"empty": true`.
+//! Reading a bound back into an inexact [`Bound`] rounds it outward.
+
+use crate::interval::parse_rational;
+use crate::{Bound, Interval, Round};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[derive(Serialize, Deserialize)]
+struct Repr {
+    lo: Option<String>,
+    hi: Option<String>,
+    #[serde(default)]
+    lo_open: bool,
+    #[serde(default)]
+    hi_open: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    empty: bool,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+fn write_bound<B: Bound>(bound: Option<&B>) -> Option<String> {
+    let n = bound?.to_rational();
+    Some(format!("{}/{}", n.numer(), n.denom()))
+}
+
+fn read_bound<B: Bound, E: Error>(bound: Option<String>, round: Round) -> Result<Option<B>, E> {
+    let Some(s) = bound else {
+        return Ok(None);
+    };
+    let n = parse_rational(&s).ok_or_else(|| E::custom(format!("bad bound {:?}", s)))?;
+    Ok(Some(B::from_rational(&n, round)).filter(Bound::is_finite))
+}
+
+impl<B: Bound> Serialize for Interval<B> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let repr = if self.is_empty() {
+            Repr {
+                lo: None,
+                hi: None,
+                lo_open: false,
+                hi_open: false,
+                empty: true,
+            }
+        } else {
+            Repr {
+                lo: write_bound(self.lo()),
+                hi: write_bound(self.hi()),
+                lo_open: self.lo_open(),
+                hi_open: self.hi_open(),
+                empty: false,
+            }
+        };
+        repr.serialize(serializer)
+    }
+}
+
+impl<'de, B: Bound> Deserialize<'de> for Interval<B> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = Repr::deserialize(deserializer)?;
+        if repr.empty {
+            return Ok(Interval::empty());
+        }
+        let lo = read_bound(repr.lo, Round::Down)?;
+        let hi = read_bound(repr.hi, Round::Up)?;
+        // an inverted interval is almost certainly a mistake, so don't quietly make it empty
+        if let (Some(l), Some(h)) = (&lo, &hi) {
+            if l > h {
+                return Err(D::Error::custom(format!(
+                    "lower bound {} is above upper bound {}",
+                    l, h
+                )));
+            }
+        }
+        Ok(Interval::bounded(lo, repr.lo_open, hi, repr.hi_open))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ival, Interval};
+
+    #[test]
+    fn test_round_trip() {
+        for i in [
+            ival("[-1/3, 2/7)"),
+            ival("(0, inf)"),
+            ival("(-inf, -5]"),
+            Interval::default(),
+            Interval::empty(),
+            ival("[123456789012345678901234567890, 123456789012345678901234567891]"),
+        ] {
+            let json = serde_json::to_string(&i).unwrap();
+            assert_eq!(
+                serde_json::from_str::<Interval>(&json).unwrap(),
+                i,
+                "{}",
+                json
+            );
+        }
+
+        let json = serde_json::to_string(&ival("(0, 1/3]")).unwrap();
+        assert_eq!(
+            json,
+            r#"{"lo":"0/1","hi":"1/3","lo_open":true,"hi_open":false}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&<Interval>::empty()).unwrap(),
+            r#"{"lo":null,"hi":null,"lo_open":false,"hi_open":false,"empty":true}"#
+        );
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let read = |s: &str| serde_json::from_str::<Interval>(s);
+        assert_eq!(
+            read(r#"{"lo": "1", "hi": null}"#).unwrap(),
+            ival("[1, inf)")
+        );
+        assert_eq!(
+            read(r#"{"lo": "0.25", "hi": "1/2"}"#).unwrap(),
+            ival("[1/4, 1/2]")
+        );
+        assert!(read(r#"{"lo": "x", "hi": null}"#).is_err());
+        assert!(read(r#"{"lo": "2", "hi": "1"}"#).is_err());
+
+        // floats round outward so they still contain the exact value
+        let third: Interval<f64> = serde_json::from_str(r#"{"lo": "1/3", "hi": "1/3"}"#).unwrap();
+        assert!(third.lo().unwrap() < third.hi().unwrap());
+        let json = serde_json::to_string(&third).unwrap();
+        assert_eq!(serde_json::from_str::<Interval<f64>>(&json).unwrap(), third);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::Rational;
+use crate::{Bound, Interval};
+use egg::Symbol;
+
+/// A range for each of several variables, i.e. a box in as many dimensions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntervalBox<B = Rational> {
+    vars: Vec<(Symbol, Interval<B>)>,
+}
+
+impl<B: Bound> IntervalBox<B> {
+    /// Later ranges for the same variable replace earlier ones.
+    pub fn new<S: Into<Symbol>>(vars: impl IntoIterator<Item = (S, Interval<B>)>) -> Self {
+        let mut new = Self { vars: vec![] };
+        for (var, interval) in vars {
+            new.set(var, interval);
+        }
+        new
+    }
+
+    /// The range of `var`, or `None` if the box doesn't have it.
+    pub fn get(&self, var: impl Into<Symbol>) -> Option<&Interval<B>> {
+        let var = var.into();
+        self.vars.iter().find(|(v, _)| *v == var).map(|(_, i)| i)
+    }
+
+    pub fn set(&mut self, var: impl Into<Symbol>, interval: Interval<B>) {
+        let var = var.into();
+        match self.vars.iter_mut().find(|(v, _)| *v == var) {
+            Some((_, old)) => *old = interval,
+            None => self.vars.push((var, interval)),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Symbol, &Interval<B>)> {
+        self.vars.iter().map(|(v, i)| (*v, i))
+    }
+
+    /// A box is empty if any of its ranges are.
+    pub fn is_empty(&self) -> bool {
+        self.vars.iter().any(|(_, i)| i.is_empty())
+    }
+
+    /// Splits the box in two along its widest range that can be split.
+    pub fn bisect(&self) -> Option<(Self, Self)> {
+        let (widest, (left, right)) = self
+            .vars
+            .iter()
+            .enumerate()
+            .filter_map(|(n, (_, i))| Some((n, i.bisect()?)))
+            .max_by(|(a, _), (b, _)| {
+                let spread = |n: &usize| self.vars[*n].1.spread();
+                spread(a).partial_cmp(&spread(b)).unwrap()
+            })?;
+        let (mut a, mut b) = (self.clone(), self.clone());
+        a.vars[widest].1 = left;
+        b.vars[widest].1 = right;
+        Some((a, b))
+    }
+
+    /// Splits every range into `n` pieces, giving `n` to the power of the dimension boxes.
+    pub fn split_n(&self, n: usize) -> Vec<Self> {
+        let mut boxes = vec![Self { vars: vec![] }];
+        for (var, interval) in &self.vars {
+            let pieces = interval.split_n(n);
+            boxes = boxes
+                .iter()
+                .flat_map(|b| {
+                    pieces.iter().map(|piece| {
+                        let mut b = b.clone();
+                        b.vars.push((*var, piece.clone()));
+                        b
+                    })
+                })
+                .collect();
+        }
+        boxes
+    }
+}
+
+/// Evaluates `f` over smaller and smaller pieces of `input` and returns the hull of the results.
+///
+/// Interval arithmetic loses precision when a variable shows up more than once,
+/// and that loss shrinks along with the ranges, so this tightens any sound `f`.
+/// Each step bisects the widest range, `depth` times in all, so `f` runs up to `2^depth` times.
+pub fn subdivide<B: Bound>(
+    input: &IntervalBox<B>,
+    depth: usize,
+    f: &impl Fn(&IntervalBox<B>) -> Interval<B>,
+) -> Interval<B> {
+    if input.is_empty() {
+        return Interval::empty();
+    }
+    match input.bisect() {
+        Some((a, b)) if depth > 0 => subdivide(&a, depth - 1, f).hull(&subdivide(&b, depth - 1, f)),
+        _ => f(input),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ival;
+
+    #[test]
+    fn test_split() {
+        let (a, b) = ival("(0, 1]").bisect().unwrap();
+        assert_eq!((a, b), (ival("(0, 1/2]"), ival("[1/2, 1]")));
+        assert_eq!(ival("[1, 1]").bisect(), None);
+        assert_eq!(ival("[1, inf)").bisect(), None);
+        assert_eq!(
+            ival("[0, 1)").split_n(3),
+            vec![ival("[0, 1/3]"), ival("[1/3, 2/3]"), ival("[2/3, 1)")]
+        );
+        assert_eq!(ival("(-inf, 1]").split_n(3), vec![ival("(-inf, 1]")]);
+
+        let tiny = Interval::<f64>::from_f64s(1.0, 1.0f64.next_up());
+        assert_eq!(tiny.bisect(), None);
+        assert_eq!(tiny.split_n(4), vec![tiny]);
+
+        let square = IntervalBox::new([("x", ival("[0, 4]")), ("y", ival("[0, 2]"))]);
+        let (left, right) = square.bisect().unwrap();
+        assert_eq!(left.get("x"), Some(&ival("[0, 2]")));
+        assert_eq!(right.get("x"), Some(&ival("[2, 4]")));
+        assert_eq!(right.get("y"), Some(&ival("[0, 2]")));
+        assert_eq!(square.split_n(2).len(), 4);
+        assert_eq!(square.get("z"), None);
+    }
+
+    #[test]
+    fn test_subdivide() {
+        // x - x is always 0, but plain interval arithmetic can't see that
+        let x_minus_x = |b: &IntervalBox| {
+            let x = b.get("x").unwrap();
+            x - x
+        };
+        let input = IntervalBox::new([("x", ival("[0, 1]"))]);
+        assert_eq!(subdivide(&input, 0, &x_minus_x), ival("[-1, 1]"));
+        assert_eq!(subdivide(&input, 3, &x_minus_x), ival("[-1/8, 1/8]"));
+
+        // bounds that can't be split are left alone
+        let input = IntervalBox::new([("x", ival("[0, inf)"))]);
+        assert_eq!(subdivide(&input, 3, &x_minus_x), Interval::default());
+    }
+}
@@ -0,0 +1,437 @@
+//! Transcendental functions on [`Interval`]s.
+//!
+//! The true results are irrational, so each bound is approximated by a
+//! rational that is rounded outward: lower bounds down and upper bounds up.
+//! That keeps the results sound no matter the precision.
+
+use crate::bound::round_to_bits;
+use crate::{Bound, Interval, Lattice, Rational, Round};
+use num::{BigInt, BigRational, One, Signed, Zero};
+
+/// The result of a function that is only defined on part of the real line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Restricted {
+    /// Encloses the function over the part of the input where it's defined.
+    pub interval: Interval,
+    /// Whether some of the input was outside the domain and got dropped.
+    pub clipped: bool,
+}
+
+// Extra bits carried through intermediate steps, so that the
+// final rounding to `bits` is what dominates the error.
+const GUARD_BITS: u32 = 16;
+
+// e^x has about 1.44 x bits before the binary point, so past this
+// `exp` gives up on exact bounds rather than build enormous rationals.
+// It's already far beyond what an f64 can hold.
+const EXP_LIMIT: i64 = 4096;
+
+fn rat(n: i64) -> BigRational {
+    BigRational::from_integer(n.into())
+}
+
+fn pow2(n: u64) -> BigInt {
+    BigInt::one() << n
+}
+
+// An enclosure of a single irrational number.
+type Enclosure = (BigRational, BigRational);
+
+fn round_out((lo, hi): Enclosure, bits: u32) -> Enclosure {
+    (
+        round_to_bits(&lo, bits, Round::Down),
+        round_to_bits(&hi, bits, Round::Up),
+    )
+}
+
+fn sqrt_point(x: &BigRational, bits: u32) -> Enclosure {
+    // sqrt(p/q) = sqrt(p*q)/q, and that's at least 1/q,
+    // so flooring sqrt(p*q) after scaling by 2^bits loses at most 2^-bits relatively
+    let n = (x.numer() * x.denom()) << (2 * bits as u64);
+    let root = n.sqrt();
+    let denom = x.denom() * pow2(bits as u64);
+    let lo = BigRational::new(root.clone(), denom.clone());
+    if &root * &root == n {
+        (lo.clone(), lo)
+    } else {
+        (lo, BigRational::new(root + 1, denom))
+    }
+}
+
+fn exp_point(x: &BigRational, bits: u32) -> Enclosure {
+    if x.is_zero() {
+        return (rat(1), rat(1));
+    }
+    // e^x = (e^(x/2^m))^(2^m), where the inner series converges quickly.
+    // Squaring m times doubles the relative error each time, so pay for it up front.
+    let mut y = x.clone();
+    let mut m = 0;
+    while y.abs() > BigRational::new(1.into(), 2.into()) {
+        y /= rat(2);
+        m += 1;
+    }
+    let bits = bits + GUARD_BITS + m;
+    let eps = BigRational::new(1.into(), pow2(bits as u64 + 1));
+
+    let (mut sum, mut term, mut k) = (rat(0), rat(1), 0);
+    while term.abs() >= eps {
+        sum += &term;
+        k += 1;
+        term = term * &y / rat(k);
+    }
+    // with |y| <= 1/2 the tail is at most twice its first term
+    let tail = term.abs() * rat(2);
+    let (mut lo, mut hi) = round_out((&sum - &tail, &sum + &tail), bits);
+    for _ in 0..m {
+        (lo, hi) = round_out((&lo * &lo, &hi * &hi), bits);
+    }
+    (lo, hi)
+}
+
+// atanh(z) = z + z^3/3 + z^5/5 + ..., for |z| <= 1/3
+fn atanh_point(z: &BigRational, bits: u32) -> Enclosure {
+    let eps = BigRational::new(1.into(), pow2(bits as u64 + 1));
+    let z2 = z * z;
+    let (mut sum, mut power, mut k) = (rat(0), z.clone(), 1);
+    while (&power / rat(k)).abs() >= eps {
+        sum += &power / rat(k);
+        power *= &z2;
+        k += 2;
+    }
+    // the tail is a geometric-ish series with ratio at most z^2 <= 1/9
+    let tail = (&power / rat(k)).abs() * rat(9) / rat(8);
+    (&sum - &tail, &sum + &tail)
+}
+
+fn ln_point(x: &BigRational, bits: u32) -> Enclosure {
+    if x.is_one() {
+        return (rat(0), rat(0));
+    }
+    // ln(2^k * y) = k ln(2) + ln(y), with y in [1, 2)
+    // and ln(y) = 2 atanh((y - 1) / (y + 1)), which converges quickly
+    let mut k = x.numer().bits() as i64 - x.denom().bits() as i64;
+    let scale = BigRational::from_integer(pow2(k.unsigned_abs()));
+    let mut y = if k >= 0 { x / scale } else { x * scale };
+    while y >= rat(2) {
+        y /= rat(2);
+        k += 1;
+    }
+    while y < rat(1) {
+        y *= rat(2);
+        k -= 1;
+    }
+
+    let inner = bits + GUARD_BITS + 64 - (k.unsigned_abs().leading_zeros());
+    let (ln2_lo, ln2_hi) = atanh_point(&BigRational::new(1.into(), 3.into()), inner);
+    let (ln2_lo, ln2_hi) = (ln2_lo * rat(2), ln2_hi * rat(2));
+    let (y_lo, y_hi) = atanh_point(&((&y - rat(1)) / (&y + rat(1))), inner);
+    let (k_lo, k_hi) = if k >= 0 {
+        (ln2_lo * rat(k), ln2_hi * rat(k))
+    } else {
+        (ln2_hi * rat(k), ln2_lo * rat(k))
+    };
+    round_out((k_lo + y_lo * rat(2), k_hi + y_hi * rat(2)), bits)
+}
+
+// atan(z) = z - z^3/3 + z^5/5 - ..., for |z| < 1
+fn atan_point(z: &BigRational, bits: u32) -> Enclosure {
+    let eps = BigRational::new(1.into(), pow2(bits as u64 + 1));
+    let z2 = z * z;
+    let (mut sum, mut power, mut k) = (rat(0), z.clone(), 1);
+    while (&power / rat(k)).abs() >= eps {
+        sum += &power / rat(k);
+        power *= -&z2;
+        k += 2;
+    }
+    // alternating and decreasing, so the tail is less than its first term
+    let tail = (&power / rat(k)).abs();
+    (&sum - &tail, &sum + &tail)
+}
+
+fn pi(bits: u32) -> Enclosure {
+    // Machin's formula: pi = 16 atan(1/5) - 4 atan(1/239)
+    let (a_lo, a_hi) = atan_point(&BigRational::new(1.into(), 5.into()), bits + 4);
+    let (b_lo, b_hi) = atan_point(&BigRational::new(1.into(), 239.into()), bits + 4);
+    round_out(
+        (
+            a_lo * rat(16) - b_hi * rat(4),
+            a_hi * rat(16) - b_lo * rat(4),
+        ),
+        bits,
+    )
+}
+
+fn trig_point(x: &BigRational, bits: u32, cos: bool) -> Enclosure {
+    // Reduce x by a multiple of 2 pi. Since pi is only known to
+    // be in a range, the reduced argument is off by up to `delta`.
+    let bits = bits + GUARD_BITS;
+    let magnitude = (x.numer().bits() as i64 - x.denom().bits() as i64).max(0) as u32;
+    let (pi_lo, pi_hi) = pi(bits + magnitude + 2);
+    let two_pi = &pi_lo + &pi_hi;
+    let k = (x / &two_pi).round();
+    let y = x - &k * &two_pi;
+    let delta = k.abs() * (&pi_hi - &pi_lo);
+
+    let eps = BigRational::new(1.into(), pow2(bits as u64 + 1));
+    let (mut sum, mut term, mut n) = if cos {
+        (rat(0), rat(1), 0)
+    } else {
+        (rat(0), y.clone(), 1)
+    };
+    // |y| <= 4, so once n is past that the terms shrink
+    while n <= 4 || term.abs() >= eps {
+        sum += &term;
+        term = -term * &y * &y / rat((n + 1) * (n + 2));
+        n += 2;
+    }
+    // Taylor's theorem bounds the tail by the first term left out,
+    // and sin and cos are 1-Lipschitz, so the reduction error just adds
+    let error = term.abs() + delta;
+    let (lo, hi) = (&sum - &error, &sum + &error);
+    round_out((lo.max(rat(-1)), hi.min(rat(1))), bits - GUARD_BITS)
+}
+
+// Builds the image of `interval` under an increasing function, given enclosures
+// of the function at each finite bound (None stays infinite).
+// A bound can only stay open if the function was computed exactly there.
+fn increasing(interval: &Interval, lo: Option<Enclosure>, hi: Option<Enclosure>) -> Interval {
+    let exact = |e: &Option<Enclosure>| e.as_ref().is_some_and(|(lo, hi)| lo == hi);
+    Interval::bounded(
+        lo.as_ref().map(|(lo, _)| lo.clone().into()),
+        interval.lo_open() && exact(&lo),
+        hi.as_ref().map(|(_, hi)| hi.clone().into()),
+        interval.hi_open() && exact(&hi),
+    )
+}
+
+impl Interval {
+    /// The square roots of the non-negative part of the interval.
+    /// Each bound is correct to roughly `bits` bits, and rounded outward.
+    pub fn sqrt(&self, bits: u32) -> Restricted {
+        self.restricted(Interval::new(Some(Rational::zero()), None), |domain| {
+            let lo = domain.lo().map(|lo| sqrt_point(&lo.to_rational(), bits));
+            let hi = domain.hi().map(|hi| sqrt_point(&hi.to_rational(), bits));
+            increasing(domain, lo, hi)
+        })
+    }
+
+    /// The natural logarithms of the positive part of the interval,
+    /// with about `bits` bits of each bound correct.
+    pub fn ln(&self, bits: u32) -> Restricted {
+        let positive = Interval::new(Some(Rational::zero()), None).with_lo_open(true);
+        self.restricted(positive, |domain| {
+            let lo = domain.lo().filter(|lo| !lo.is_zero());
+            let lo = lo.map(|lo| ln_point(&lo.to_rational(), bits));
+            let hi = domain.hi().map(|hi| ln_point(&hi.to_rational(), bits));
+            increasing(domain, lo, hi)
+        })
+    }
+
+    /// `e` raised to each number in the interval,
+    /// with bounds rounded outward after about `bits` correct bits.
+    ///
+    /// Bounds beyond ±4096 aren't computed: the result just
+    /// goes up to infinity, or down to 0, from there.
+    pub fn exp(&self, bits: u32) -> Interval {
+        if self.is_empty() {
+            return Interval::empty();
+        }
+        // e^x is increasing, so pulling a lower bound down to the limit
+        // (or an upper bound up to it) only loosens the result.
+        // The other way around, the bound becomes 0 or infinity instead.
+        let limit = rat(EXP_LIMIT);
+        let exp_clamped = |x: &BigRational| exp_point(x.clamp(&-&limit, &limit), bits);
+        let lo = self
+            .lo()
+            .map(|lo| lo.to_rational())
+            .map(|lo| match exp_clamped(&lo) {
+                (_, hi) if lo < -&limit => (rat(0), hi),
+                enclosure => enclosure,
+            });
+        let hi = self
+            .hi()
+            .map(|hi| hi.to_rational())
+            .filter(|hi| *hi <= limit);
+        let hi = hi.as_ref().map(exp_clamped);
+        let image = increasing(self, lo, hi);
+        if self.lo().is_none() {
+            // e^x only approaches 0 as x goes to -inf
+            image.intersect(&Interval::new(Some(Rational::zero()), None).with_lo_open(true))
+        } else {
+            image
+        }
+    }
+
+    /// The sines of the numbers in the interval.
+    /// `bits` is how precise the bounds are, roughly, before rounding outward.
+    pub fn sin(&self, bits: u32) -> Interval {
+        // the peaks are at pi/2 + 2k pi, and the troughs at -pi/2 + 2k pi
+        self.trig(bits, false, BigRational::new(1.into(), 2.into()))
+    }
+
+    /// The cosines of the numbers in the interval,
+    /// to about `bits` bits like [`sin`](Interval::sin).
+    pub fn cos(&self, bits: u32) -> Interval {
+        // the peaks are at 2k pi, and the troughs at pi + 2k pi
+        self.trig(bits, true, rat(0))
+    }
+
+    // `peak` is where the function is 1, in units of pi.
+    // It's -1 exactly one pi away from there.
+    fn trig(&self, bits: u32, cos: bool, peak: BigRational) -> Interval {
+        let unit = Interval::new(Some(Rational::from(-1)), Some(Rational::from(1)));
+        if self.is_empty() {
+            return Interval::empty();
+        }
+        let (Some(a), Some(b)) = (self.lo(), self.hi()) else {
+            return unit;
+        };
+        let (a, b) = (&a.to_rational(), &b.to_rational());
+        let (pi_lo, pi_hi) = pi(bits + GUARD_BITS);
+        if b - a >= &pi_lo * rat(2) {
+            return unit;
+        }
+
+        let (a_lo, a_hi) = trig_point(a, bits, cos);
+        let (b_lo, b_hi) = trig_point(b, bits, cos);
+        let (mut lo, mut hi) = (a_lo.min(b_lo), a_hi.max(b_hi));
+
+        // Check every peak and trough that could be near [a, b].
+        // Each one is only known to be in a range, so be conservative.
+        let might_hit = |coef: &BigRational| {
+            let (at_lo, at_hi) = if coef.is_negative() {
+                (coef * &pi_hi, coef * &pi_lo)
+            } else {
+                (coef * &pi_lo, coef * &pi_hi)
+            };
+            &at_lo <= b && &at_hi >= a
+        };
+        let first = (a / (&pi_lo * rat(2))).floor().to_integer() - 2;
+        let last = (b / (&pi_lo * rat(2))).ceil().to_integer() + 2;
+        let mut k = first;
+        while k <= last {
+            let two_k = BigRational::from_integer(&k * 2);
+            if might_hit(&(&peak + &two_k)) {
+                hi = rat(1);
+            }
+            if might_hit(&(&peak + rat(1) + &two_k)) {
+                lo = rat(-1);
+            }
+            k += 1;
+        }
+        Interval::new(Some(lo.into()), Some(hi.into()))
+    }
+
+    // Runs `f` on the part of the interval within `domain`.
+    fn restricted(&self, domain: Interval, f: impl FnOnce(&Interval) -> Interval) -> Restricted {
+        let defined = self.intersect(&domain);
+        let clipped = !self.leq(&defined);
+        let interval = if defined.is_empty() {
+            Interval::empty()
+        } else {
+            f(&defined)
+        };
+        Restricted { interval, clipped }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ival;
+
+    // checks that `i` is within the tight `bounds`, which contain the true value
+    fn assert_within(i: &Interval, bounds: &str) {
+        assert!(i.leq(&ival(bounds)), "{} is not within {}", i, bounds);
+    }
+
+    #[test]
+    fn test_sqrt() {
+        let root2 = ival("[2, 2]").sqrt(64);
+        assert!(!root2.clipped);
+        assert_within(
+            &root2.interval,
+            "[1.414213562373095048, 1.414213562373095049]",
+        );
+
+        assert_eq!(
+            ival("[4, 9]").sqrt(64),
+            Restricted {
+                interval: ival("[2, 3]"),
+                clipped: false
+            }
+        );
+        let partly_negative = ival("[-4, 9)").sqrt(64);
+        assert!(partly_negative.clipped);
+        assert_eq!(partly_negative.interval, ival("[0, 3)"));
+        assert_eq!(ival("(1/4, inf)").sqrt(8).interval, ival("(1/2, inf)"));
+        assert_eq!(ival("[-2, -1]").sqrt(8).interval, Interval::empty());
+    }
+
+    #[test]
+    fn test_exp_ln() {
+        let e = ival("[1, 1]").exp(64);
+        assert_within(&e, "[2.718281828459045235, 2.718281828459045236]");
+        assert_eq!(ival("(-inf, 0]").exp(64), ival("(0, 1]"));
+
+        let big = ival("[100, 100]").exp(64);
+        assert_within(&big, "[26881171418161354000000000000000000000000000, 26881171418161355000000000000000000000000000]");
+        let tiny = ival("[-100, -100]").exp(64);
+        assert_within(&tiny, "[0.0000000000000000000000000000000000000000000372007597602083, 0.0000000000000000000000000000000000000000000372007597602084]");
+
+        // huge arguments give up on one side instead of running out of memory
+        let huge = ival("[10000000, 10000000]").exp(64);
+        assert!(huge.hi().is_none() && huge.lo() > Some(&rat(10).pow(1000).into()));
+        let huge = ival("[-10000000, -10000000]").exp(64);
+        assert!(
+            huge.lo() == Some(&Rational::zero()) && huge.hi() < Some(&rat(10).pow(-1000).into())
+        );
+        assert_eq!(
+            ival("(-1000000000, inf)").exp(8).lo(),
+            Some(&Rational::zero())
+        );
+
+        let ln2 = ival("[2, 2]").ln(64);
+        assert!(!ln2.clipped);
+        assert_within(
+            &ln2.interval,
+            "[0.693147180559945309, 0.693147180559945310]",
+        );
+        let ln = ival("[1/1024, 10]").ln(64).interval;
+        assert_within(
+            &Interval::new(ln.hi().cloned(), ln.hi().cloned()),
+            "[2.302585092994045684, 2.302585092994045685]",
+        );
+        assert_within(
+            &Interval::new(ln.lo().cloned(), ln.lo().cloned()),
+            "[-6.931471805599453095, -6.931471805599453094]",
+        );
+
+        let non_positive = ival("[-1, 1]").ln(64);
+        assert!(non_positive.clipped);
+        assert_eq!(non_positive.interval, ival("(-inf, 0]"));
+        assert_eq!(ival("(0, 1)").ln(64).interval, ival("(-inf, 0)"));
+    }
+
+    #[test]
+    fn test_trig() {
+        let sin = ival("[0, 4]").sin(64);
+        assert_eq!(sin.hi(), Some(&Rational::one()));
+        assert_within(
+            &Interval::new(sin.lo().cloned(), sin.lo().cloned()),
+            "[-0.756802495307928252, -0.756802495307928251]",
+        );
+        let cos = ival("[-1, 1]").cos(64);
+        assert_eq!(cos.hi(), Some(&Rational::one()));
+        assert_within(
+            &Interval::new(cos.lo().cloned(), cos.lo().cloned()),
+            "[0.540302305868139717, 0.540302305868139718]",
+        );
+        assert_eq!(ival("[0, 7]").sin(16), ival("[-1, 1]"));
+        assert_eq!(ival("[0, inf)").cos(16), ival("[-1, 1]"));
+        assert_within(
+            &ival("[1000, 1000]").sin(64),
+            "[0.826879540532002, 0.826879540532004]",
+        );
+    }
+}
@@ -0,0 +1,53 @@
+// The e-class analysis that uses affine forms instead of intervals,
+// to see how much equality saturation adds on top of a domain
+// that already tracks correlations between variables.
+use egg::*;
+use egg_tutorial_pldi_2022::*;
+
+// Returns the range before and after running the rules.
+fn optimize_affine(s: &str, intervals: &[(&str, &str)]) -> (Interval, Interval) {
+    let expr = parse_math(s).unwrap();
+    let mut runner = Runner::<Math, AffineAnalysis, ()>::default().with_expr(&expr);
+    let root = runner.roots[0];
+
+    for (e, interval) in intervals {
+        let e = parse_math(e).unwrap();
+        let id = runner.egraph.add_expr(&e);
+        // each variable gets its own noise symbol
+        runner
+            .egraph
+            .set_analysis_data(id, Affine::from_interval(&ival(interval)))
+    }
+    runner.egraph.rebuild();
+
+    let range = |runner: &Runner<Math, AffineAnalysis>| {
+        runner.egraph[root]
+            .data
+            .as_ref()
+            .map_or_else(Interval::default, Affine::to_interval)
+    };
+    let initial = range(&runner);
+    runner = runner.run(&affine_rules());
+    let result = range(&runner);
+    println!("Affine: {} before, {} after", initial, result);
+    (initial, result)
+}
+
+#[test]
+fn correlations_without_rules() {
+    // plain intervals get [0, 1] for this before any rewriting
+    let (initial, _) = optimize_affine("(* x (- 1 x))", &[("x", "0, 1")]);
+    assert_eq!(initial, ival("0, 1/2"));
+}
+
+#[test]
+fn paper_example() {
+    // intervals start at [-3, 1/3], and rewriting gets them down to [-1, 0]
+    let (initial, result) =
+        optimize_affine("(- 1 (/ (* 2 y) (+ x y)))", &[("x", "0, 1"), ("y", "1, 2")]);
+    assert_eq!(initial, ival("-8/3, 2/3"));
+    assert!(ival("-1, 0").is_subset_of(&result));
+    // picking one side on merge is weaker than intersecting,
+    // so this doesn't get all the way down to [-1, 0] like intervals do
+    assert_eq!(result, ival("-29/18, 11/18"));
+}
@@ -0,0 +1,73 @@
+// The interval analysis from part 2 is generic over the kind of bound,
+// so the same e-graph can run on exact rationals or on fast floats.
+use egg::*;
+use egg_tutorial_pldi_2022::*;
+
+// Picking the backend is just a matter of picking `B`
+fn optimize_interval<B: Bound + 'static>(s: &str, intervals: &[(&str, &str)]) -> Interval<B> {
+    optimize_with(IntervalAnalysis::default(), s, intervals)
+}
+
+fn optimize_with<B: Bound + 'static>(
+    analysis: IntervalAnalysis<B>,
+    s: &str,
+    intervals: &[(&str, &str)],
+) -> Interval<B> {
+    let expr = parse_math(s).unwrap();
+    let mut runner = Runner::<Math, IntervalAnalysis<B>, ()>::new(analysis).with_expr(&expr);
+    let root = runner.roots[0];
+
+    for (e, interval) in intervals {
+        let e = parse_math(e).unwrap();
+        let id = runner.egraph.add_expr(&e);
+        runner
+            .egraph
+            .set_analysis_data(id, Interval::ival(interval))
+    }
+
+    runner.egraph.rebuild();
+    runner = runner.run(&interval_rules());
+    runner.egraph[root].data.clone()
+}
+
+const PAPER_EXAMPLE: &str = "(- 1 (/ (* 2 y) (+ x y)))";
+
+#[test]
+fn paper_example_exact() {
+    let result = optimize_interval::<Rational>(PAPER_EXAMPLE, &[("x", "0, 1"), ("y", "1, 2")]);
+    assert_eq!(result, ival("-1, 0"));
+    // plain big rationals get exactly the same answer, just slower
+    let big = optimize_interval::<num::BigRational>(PAPER_EXAMPLE, &[("x", "0, 1"), ("y", "1, 2")]);
+    assert_eq!(big, Interval::ival("-1, 0"));
+}
+
+#[test]
+fn paper_example_f64() {
+    let result = optimize_interval::<f64>(PAPER_EXAMPLE, &[("x", "0, 1"), ("y", "1, 2")]);
+    // rounding can only ever make it a little wider
+    assert!(Interval::ival("-1, 0").leq(&result));
+    assert!(result.leq(&Interval::ival("-1.000001, 0.000001")));
+}
+
+#[test]
+fn thirds_f64() {
+    // 1/3 can't be a float, but the interval around it still works
+    let result = optimize_interval::<f64>("(* 3 (/ x 3))", &[("x", "1/3, 2/3")]);
+    assert!(Interval::ival("1/3, 2/3").leq(&result));
+    assert!(result.leq(&Interval::ival("0.333333, 0.666667")));
+}
+
+#[test]
+fn simplified_bounds() {
+    // every multiplication and division makes the exact bounds longer
+    let expr = "(/ (* x x) (+ x 1))";
+    let vars = [("x", "12345/67891, 23456/78901")];
+    let exact = optimize_interval::<Rational>(expr, &vars);
+    let simplified = optimize_with(IntervalAnalysis::default().with_bits(8), expr, &vars);
+    // simplifying only ever makes things a little wider
+    assert!(exact.leq(&simplified));
+    assert!(simplified.leq(&ival("0, 1/10")));
+    assert!(exact.lo().unwrap().to_rational().denom().bits() > 32);
+    let lo = simplified.lo().unwrap().to_rational();
+    assert!(lo.numer().bits() <= 9, "{} isn't simple", lo);
+}
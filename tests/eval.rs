@@ -0,0 +1,90 @@
+// Checks the optimizer against plain evaluation:
+// whatever it extracts has to mean the same thing as what went in.
+use egg::*;
+use egg_tutorial_pldi_2022::*;
+use num::BigRational;
+use std::collections::HashMap;
+
+fn optimize(expr: &RecExpr<Math>) -> RecExpr<Math> {
+    let runner = Runner::<Math, ConstantFold, ()>::default()
+        .with_expr(expr)
+        .with_iter_limit(8)
+        .run(&math_rules());
+    let extractor = Extractor::new(&runner.egraph, AstSize);
+    extractor.find_best(runner.roots[0]).1
+}
+
+fn envs() -> Vec<HashMap<Symbol, BigRational>> {
+    let q = |n: i64, d: i64| BigRational::new(n.into(), d.into());
+    let values = [q(0, 1), q(1, 1), q(-3, 2), q(2, 7)];
+    let mut envs = vec![];
+    for x in &values {
+        for y in &values {
+            envs.push(HashMap::from([
+                ("x".into(), x.clone()),
+                ("y".into(), y.clone()),
+            ]));
+        }
+    }
+    envs
+}
+
+#[test]
+fn optimizer_preserves_meaning() {
+    for s in [
+        "(- 1 (/ (* 2 y) (+ x y)))",
+        "(+ x (+ x (+ x x)))",
+        "(* (+ x 3) (+ x 1))",
+        "(+ 1 (- y (* (- 2 1) y)))",
+        "(/ (* x y) (+ 1 1))",
+    ] {
+        let expr: RecExpr<Math> = s.parse().unwrap();
+        let best = optimize(&expr);
+        for env in envs() {
+            // an error on only one side would mean the rules
+            // changed where the expression is defined
+            assert_eq!(
+                eval(&expr, &env).is_ok(),
+                eval(&best, &env).is_ok(),
+                "{} and {} disagree on {:?}",
+                expr,
+                best,
+                env
+            );
+            if let (Ok(a), Ok(b)) = (eval(&expr, &env), eval(&best, &env)) {
+                assert_eq!(a, b, "{} and {} disagree on {:?}", expr, best, env);
+            }
+        }
+    }
+}
+
+#[test]
+fn constant_fold_agrees() {
+    // on closed expressions, the analysis and the evaluator are the same thing
+    for s in [
+        "(- 1 (/ (* 2 3) (+ 1/2 3)))",
+        "(* 7 (/ 1 3))",
+        "(/ 1 (- 2 2))",
+    ] {
+        let expr: RecExpr<Math> = s.parse().unwrap();
+        let mut egraph = EGraph::<Math, ConstantFold>::default();
+        let id = egraph.add_expr(&expr);
+        assert_eq!(egraph[id].data, eval(&expr, &HashMap::new()).ok(), "{}", s);
+    }
+}
+
+#[test]
+fn interval_eval_matches_rebuild() {
+    // before any rewrites, the analysis is just interval arithmetic on the tree
+    let expr: RecExpr<Math> = "(- 1 (/ (* 2 y) (+ x (* y y))))".parse().unwrap();
+    let ranges = HashMap::from([("x".into(), ival("0, 1")), ("y".into(), ival("1/2, 2"))]);
+
+    let mut egraph = EGraph::<Math, IntervalAnalysis>::default();
+    let root = egraph.add_expr(&expr);
+    for (var, interval) in &ranges {
+        let id = egraph.lookup(Math::Var(*var)).unwrap();
+        egraph.set_analysis_data(id, interval.clone());
+    }
+    egraph.rebuild();
+    assert_eq!(egraph[root].data, interval_eval(&expr, &ranges));
+}
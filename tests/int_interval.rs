@@ -0,0 +1,135 @@
+// The interval analysis from part 2, but for 32-bit machine integers.
+// Everything wraps around on overflow, so the rules still hold,
+// and the analysis can tell when a rewrite gets rid of an overflow.
+use egg::*;
+use egg_tutorial_pldi_2022::*;
+
+define_language! {
+    enum Math {
+        Num(i128),
+        "+" = Add([Id; 2]),
+        "-" = Sub([Id; 2]),
+        "*" = Mul([Id; 2]),
+        Var(Symbol),
+    }
+}
+
+// Two different things can be said about overflow in an e-class.
+// The interval's own flag is met like the rest of the interval, so it means
+// "some term in the class is overflow-free", which is what a rewrite buys you.
+// `overflowed` is ORed instead, and means "some term in the class, like the one
+// we started from, may overflow somewhere inside it".
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Data {
+    interval: I32Interval,
+    overflowed: bool,
+}
+
+impl Data {
+    fn new(interval: I32Interval) -> Self {
+        Self {
+            interval,
+            overflowed: false,
+        }
+    }
+}
+
+#[derive(Default)]
+struct IntAnalysis;
+
+impl Analysis<Math> for IntAnalysis {
+    type Data = Data;
+
+    fn make(egraph: &EGraph<Math, Self>, enode: &Math) -> Self::Data {
+        let get = |id: &Id| &egraph[*id].data.interval;
+        let interval = match enode {
+            Math::Num(n) => I32Interval::singleton(*n),
+            Math::Add([a, b]) => get(a) + get(b),
+            Math::Sub([a, b]) => get(a) - get(b),
+            Math::Mul([a, b]) => get(a) * get(b),
+            Math::Var(_) => I32Interval::full(),
+        };
+        // this term overflows if any of its children do, even once they've been
+        // folded into a literal that doesn't, so look at their bits too
+        let child_overflowed = |c: &Id| egraph[*c].data.overflowed;
+        let overflowed = interval.may_overflow() || enode.children().iter().any(child_overflowed);
+        Data {
+            interval,
+            overflowed,
+        }
+    }
+
+    fn merge(&mut self, to: &mut Self::Data, from: Self::Data) -> DidMerge {
+        assert!(
+            !to.interval.intersect(&from.interval).is_empty(),
+            "bad merge! {} and {} are disjoint",
+            to.interval,
+            from.interval
+        );
+        let overflowed = DidMerge(
+            !to.overflowed && from.overflowed,
+            to.overflowed && !from.overflowed,
+        );
+        to.overflowed |= from.overflowed;
+        merge_meet(&mut to.interval, from.interval) | overflowed
+    }
+
+    fn modify(egraph: &mut EGraph<Math, Self>, id: Id) {
+        if let Some(constant) = egraph[id].data.interval.get_constant() {
+            let new_id = egraph.add(Math::Num(constant));
+            egraph.union(id, new_id);
+        }
+    }
+}
+
+#[rustfmt::skip]
+fn rules() -> Vec<Rewrite<Math, IntAnalysis>> {
+    vec![
+        rewrite!("comm-add";  "(+ ?a ?b)"        => "(+ ?b ?a)"),
+        rewrite!("comm-mul";  "(* ?a ?b)"        => "(* ?b ?a)"),
+        rewrite!("assoc-add"; "(+ ?a (+ ?b ?c))" => "(+ (+ ?a ?b) ?c)"),
+        rewrite!("cancel-sub"; "(- (+ ?a ?b) ?b)" => "?a"),
+        rewrite!("distribute"; "(* ?a (+ ?b ?c))" => "(+ (* ?a ?b) (* ?a ?c))"),
+        rewrite!("factor";     "(+ (* ?a ?b) (* ?a ?c))" => "(* ?a (+ ?b ?c))"),
+    ]
+}
+
+fn analyze(s: &str, intervals: &[(&str, I32Interval)]) -> (Data, Data) {
+    let expr: RecExpr<Math> = s.parse().unwrap();
+    let mut runner = Runner::<Math, IntAnalysis, ()>::default().with_expr(&expr);
+    let root = runner.roots[0];
+    for (var, interval) in intervals {
+        let id = runner.egraph.add(Math::Var(Symbol::from(*var)));
+        runner.egraph.set_analysis_data(id, Data::new(*interval));
+    }
+    runner.egraph.rebuild();
+    let initial = runner.egraph[root].data;
+    runner = runner.run(&rules());
+    (initial, runner.egraph[root].data)
+}
+
+#[test]
+fn overflow_goes_away() {
+    // x + x overflows, but subtracting x again gets back to just x
+    let x = I32Interval::new(0, 1 << 30);
+    let (initial, last) = analyze("(- (+ x x) x)", &[("x", x)]);
+    assert!(initial.interval.may_overflow());
+    assert_eq!(last.interval, x);
+    // but the term we started with still does
+    assert!(last.overflowed);
+}
+
+#[test]
+fn wraps_around() {
+    let x = I32Interval::new(0, 10);
+    let (initial, _) = analyze("(+ x 2147483647)", &[("x", x)]);
+    assert_eq!(initial.interval, I32Interval::full().with_overflow(true));
+    assert!(initial.overflowed);
+
+    // constants wrap too, and folding them into a literal
+    // doesn't hide that the original sum overflows
+    let (initial, _) = analyze("(* 2 (+ 2147483647 1))", &[]);
+    assert_eq!(initial.interval.get_constant(), Some(0));
+    assert!(!initial.interval.may_overflow());
+    assert!(initial.overflowed);
+}
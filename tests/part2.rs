@@ -55,7 +55,7 @@ impl Analysis<Math> for IntervalAnalysis {
     // We want to do interval intersection.
     // One way (commented out) is to just do the intersection and return a
     // conservative approximation of the DidMerge.
-    // Instead, we use the combinators to manually do the intersection
+    // Instead, we compare the intersection against both sides
     // in a way that returns precise merge information
     fn merge(&mut self, to: &mut Self::Data, from: Self::Data) -> DidMerge {
         // // a conservative manual implementation
@@ -66,16 +66,26 @@ impl Analysis<Math> for IntervalAnalysis {
         //     DidMerge(true, true)
         // }
 
-        // a more precise implementation using combinators
-        // note how bitwise or operator can be used to combine DidMerge's
-        egg::merge_option(&mut to.lo, from.lo, egg::merge_max)
-            | egg::merge_option(&mut to.hi, from.hi, egg::merge_min)
+        // If the intersection is empty, we just proved two things equal that
+        // can't possibly have the same value. Either a rule is unsound or
+        // the intervals we started with are infeasible, so we crash
+        // just like ConstantFold did in part1.
+        let merged = to.intersect(&from);
+        assert!(
+            !merged.is_empty(),
+            "bad merge! {} and {} are disjoint",
+            to,
+            from
+        );
+        let did_merge = DidMerge(&merged != to, merged != from);
+        *to = merged;
+        did_merge
     }
 
     fn modify(egraph: &mut EGraph<Math, Self>, id: Id) {
         // If the interval only includes one number, we can do constant folding
         if let Some(constant) = egraph[id].data.get_constant().cloned() {
-            let new_id = egraph.add(Math::Num(constant));
+            let new_id = egraph.add(Math::Num(constant.into()));
             egraph.union(id, new_id);
         }
     }
@@ -229,7 +239,7 @@ fn optimize_expr_from_egraphs_paper() {
 // and you can use this to write your own tests later.
 fn optimize_interval(s: &str, intervals: &[(&str, &str)]) -> Interval {
     let expr: RecExpr<Math> = s.parse().unwrap();
-    let mut runner = Runner::default().with_expr(&expr);
+    let mut runner = Runner::<Math, IntervalAnalysis, ()>::default().with_expr(&expr);
     let root = runner.roots[0];
 
     for (e, interval) in intervals {
@@ -242,12 +252,15 @@ fn optimize_interval(s: &str, intervals: &[(&str, &str)]) -> Interval {
     // we have to call rebuild to propagate the updated
     // analysis information throughout the e-graph
     runner.egraph.rebuild();
-    println!("Initial interval: {}", runner.egraph[root].data);
+    let initial_interval = runner.egraph[root].data.clone();
+    println!("Initial interval: {}", initial_interval);
 
     runner = runner.run(&rules());
 
     let final_interval = runner.egraph[root].data.clone();
+    let (before, after) = (initial_interval.spread(), final_interval.spread());
     println!("Final interval:   {}", final_interval);
+    println!("Width went from {} to {}", before, after);
     final_interval
 }
 
@@ -15,26 +15,15 @@ use num::*;
 // This is just a type alias for the BigRational type.
 type Num = num::BigRational;
 
-// Now we define the e-node type.
+// Now we need an e-node type.
 // E-nodes implement the `egg::Language` trait, and they are typically some
 // pairing of a operator and a list of children (as `Id`s).
 //
 // The `define_language` macro makes it easy for you to make your own
 // `Language`s from a Rust enum. This implements some nice parsing for you as well.
-define_language! {
-    enum Math {
-        // This parses rationals as enums, like "4"
-        Num(Num),
-        // This will parse a "+" symbol as an Add with two children
-        // Note that the value of this variant is a [Id; 2], or an array of 2 Id's.
-        "+" = Add([Id; 2]),
-        "-" = Sub([Id; 2]),
-        "*" = Mul([Id; 2]),
-        "/" = Div([Id; 2]),
-        // Finally, this will parse anything else as a Var, like "foo"
-        Var(Symbol),
-    }
-}
+// The `Math` language we use here is defined that way in `src/math.rs`,
+// and we get it from this crate:
+use egg_tutorial_pldi_2022::{parse_math, ConstantFold, Math};
 
 #[test]
 fn insert_some_math_into_an_egraph() {
@@ -87,98 +76,10 @@ fn rules() -> Vec<Rewrite<Math, ConstantFold>> {
     ]
 }
 
-// Now let's make the constant fold analysis.
-// The analysis itself (the ConstantFold struct) is a zero-sized type with
-// no data in it, it's just a marker.
-//
-// Make sure to derive Default, since we want egg to be able to automatically
-// construct a (the only) value of this type.
-#[derive(Default)]
-struct ConstantFold;
-
-// Here's the actual implementation.
-// Analysis is the egg trait for e-class analyses. It is parameterized over the
-// Language that the analysis is for.
-impl Analysis<Math> for ConstantFold {
-    // This associated type tells you what type is attached to
-    // each e-class. We'll use an optional number to indicate the
-    // constant (maybe) associated with each e-class.
-    type Data = Option<Num>;
-
-    // This function tells egg how to construct a `Data` for a particular e-node.
-    // It's typically where most of your logic in an e-class analysis goes.
-    fn make(egraph: &EGraph<Math, Self>, enode: &Math) -> Self::Data {
-        // first, we make a getter function that grabs the data for a given e-class id
-        let get = |id: &Id| egraph[*id].data.as_ref();
-
-        // now, we write the evaluator. Since the `Data` type is an `Option`, we
-        // can use the `?` operator in Rust, which trys to unpack the
-        // preceding optional value, "bailing" from the enclosing function if it's None
-        match enode {
-            Math::Num(n) => Some(n.clone()),
-            Math::Add([a, b]) => Some(get(a)? + get(b)?),
-            Math::Sub([a, b]) => Some(get(a)? - get(b)?),
-            Math::Mul([a, b]) => Some(get(a)? * get(b)?),
-            Math::Div([a, b]) => {
-                let b = get(b)?;
-                if !b.is_zero() {
-                    Some(get(a)? / b)
-                } else {
-                    None
-                }
-            }
-            Math::Var(_) => None,
-        }
-    }
-
-    // This function tells egg how to merge analysis data when
-    // e-classes get unioned. `to` is a mutable reference, which should
-    // be mutated as needed to be the Analysis data ofthe merged eclass.
-    // The return value is a `DidMerge`, which tells
-    // egg which "way" the merge went. It can be computed from the partial
-    // ordering over the `Data`.
-    fn merge(&mut self, to: &mut Self::Data, from: Self::Data) -> DidMerge {
-        match (to.as_mut(), from) {
-            // Neither side is known to be a constant so there's nothing
-            // to do when they merge.
-            (None, None) => DidMerge(false, false),
-
-            // Both sides are constants, so we should just make sure
-            // they're the same.
-            (Some(a), Some(b)) => {
-                assert_eq!(a, &b, "bad merge!");
-                DidMerge(false, false)
-            }
-
-            // The right side is a constant, so update `to` to be the same.
-            (None, Some(x)) => {
-                *to = Some(x);
-                DidMerge(true, false)
-            }
-
-            // The left side is a constant and the right is not, so there's
-            // nothing to do when they merge.
-            (Some(_), None) => DidMerge(false, false),
-        }
-        // Since Analysis data are so often Options, egg provides a
-        // combinator to make it easy to merge options:
-        // egg::merge_option(to, from, |a, b| {
-        //     assert_eq!(a, &b, "bad merge!");
-        //     DidMerge(false, false)
-        // })
-    }
-
-    // This is an optional function that allows you to modify the e-graph itself
-    // in response to changing e-class analysis values (or anything else).
-    // Here, we use it to actually insert new e-nodes corresponding to computed
-    // constant values.
-    fn modify(egraph: &mut EGraph<Math, Self>, id: Id) {
-        if let Some(n) = egraph[id].data.clone() {
-            let id2 = egraph.add(Math::Num(n));
-            egraph.union(id, id2);
-        }
-    }
-}
+// The ConstantFold analysis these rules use lives in `src/analysis.rs`.
+// It's a zero-sized marker type, and its `Analysis` implementation
+// tells egg how to make, merge, and act on the optional constant for each e-class.
+// Go have a look!
 
 // Now, we can write some tests the prove the equivalence of terms.
 // We use the provided `test_fn` macro from egg to quickly generate such tests.
@@ -223,8 +124,9 @@ egg::test_fn! {
 // For the sake of this demo, this function will be string -> string,
 // but it semantically takes and returns RecExpr<Math>.
 fn optimize(s: &str) -> String {
-    // parse the given expression
-    let expr: RecExpr<Math> = s.parse().unwrap();
+    // parse the given expression, written either as an s-expression
+    // or the usual way, like `1 - 2*y/(x + y)`
+    let expr = parse_math(s).unwrap();
 
     // Now we create a Runner to actually do the equality saturation.
     // Runner have a builder-style set of methods to customize them.
@@ -254,6 +156,7 @@ fn optimize(s: &str) -> String {
 #[test]
 fn test_optimize() {
     assert_eq!(optimize("(+ 1 1)"), "2");
+    assert_eq!(optimize("1 + 1"), "2");
 
     // this can be a little dicey,
     // there might be many equally "optimized" results
@@ -13,125 +13,22 @@
 use egg::*;
 use egg_tutorial_pldi_2022::*;
 
-type Num = num::BigRational;
-
-define_language! {
-    enum Math {
-        Num(Num),
-        "+" = Add([Id; 2]),
-        "-" = Sub([Id; 2]),
-        "*" = Mul([Id; 2]),
-        "/" = Div([Id; 2]),
-        Var(Symbol),
-    }
-}
-
-// Same as part1, the actual IntervalAnalysis type doesn't hold any data
-#[derive(Default)]
-struct IntervalAnalysis;
-
-// Now, to do the actual interval analysis!
-impl Analysis<Math> for IntervalAnalysis {
-    // We don't need an option for the e-class data type, since
-    // there is a reasonable default value (-inf, inf)
-    type Data = Interval;
-
-    // The make is very similar to the ConstantFold::make from part1.
-    // We don't need to use `?`, since we can just use the default interval.
-    fn make(egraph: &EGraph<Math, Self>, enode: &Math) -> Self::Data {
-        // getter function similar to before
-        let get = |id: &Id| &egraph[*id].data;
-        match enode {
-            Math::Num(n) => Interval::singleton(n.clone()),
-            Math::Add([a, b]) => get(a) + get(b),
-            Math::Sub([a, b]) => get(a) - get(b),
-            Math::Mul([a, b]) => get(a) * get(b),
-            Math::Div([a, b]) => get(a) / get(b),
-            _ => Interval::default(),
-        }
-    }
-
-    // The merge function is more complicated than the ConstantFold::merge.
-    // We want to do interval intersection.
-    // One way (commented out) is to just do the intersection and return a
-    // conservative approximation of the DidMerge.
-    // Instead, we use the combinators to manually do the intersection
-    // in a way that returns precise merge information
-    fn merge(&mut self, to: &mut Self::Data, from: Self::Data) -> DidMerge {
-        // // a conservative manual implementation
-        // if to == &from {
-        //     DidMerge(false, false)
-        // } else {
-        //     *to = to.intersect(&from);
-        //     DidMerge(true, true)
-        // }
-
-        // a more precise implementation using combinators
-        // note how bitwise or operator can be used to combine DidMerge's
-        egg::merge_option(&mut to.lo, from.lo, egg::merge_max)
-            | egg::merge_option(&mut to.hi, from.hi, egg::merge_min)
-    }
-
-    fn modify(egraph: &mut EGraph<Math, Self>, id: Id) {
-        // If the interval only includes one number, we can do constant folding
-        if let Some(constant) = egraph[id].data.get_constant().cloned() {
-            let new_id = egraph.add(Math::Num(constant));
-            egraph.union(id, new_id);
-        }
-    }
-}
-
-#[rustfmt::skip]
+// The `Math` language is the same as in part1, so it lives in `src/math.rs` now.
+//
+// The interval analysis is in `src/analysis.rs`. Like ConstantFold, it makes
+// data for each e-node by evaluating it, just with intervals instead of numbers.
+// Merging intersects the two intervals, since both are true of the e-class,
+// and if the interval ever narrows down to a single number, `modify` adds
+// that constant to the e-class, just like constant folding.
+//
+// The rules are in `src/rules.rs`. Most of them don't care about the analysis,
+// but with intervals we can finally make the division rules sound:
+// `x / x` is only 1 if the interval for `x` doesn't contain zero.
+// Check out `is_non_zero` to see how a rule can look at analysis data.
+//
+// Intervals can have other kinds of bounds too, so we pin down the default ones here.
 fn rules() -> Vec<Rewrite<Math, IntervalAnalysis>> {
-    vec![
-        rewrite!("comm-add";  "(+ ?a ?b)"        => "(+ ?b ?a)"),
-        rewrite!("comm-mul";  "(* ?a ?b)"        => "(* ?b ?a)"),
-        rewrite!("assoc-add"; "(+ ?a (+ ?b ?c))" => "(+ (+ ?a ?b) ?c)"),
-        rewrite!("assoc-mul"; "(* ?a (* ?b ?c))" => "(* (* ?a ?b) ?c)"),
-    
-        rewrite!("sub-canon"; "(- ?a ?b)" => "(+ ?a (* -1 ?b))"),
-        rewrite!("canon-sub"; "(+ ?a (* -1 ?b))" => "(- ?a ?b)"),
-        rewrite!("cancel-sub"; "(- ?a ?a)" => "0"),
-
-        rewrite!("flip-sub"; "(- ?a ?b)" => "(* -1 (- ?b ?a))"),
-
-        rewrite!("add2-mul"; "(+ ?a ?a)" => "(* 2 ?a)"),
-        rewrite!("mul-add2"; "(* 2 ?a)"  => "(+ ?a ?a)"),
-    
-        rewrite!("zero-add"; "(+ ?a 0)" => "?a"),
-        rewrite!("zero-mul"; "(* ?a 0)" => "0"),
-        rewrite!("one-mul";  "(* ?a 1)" => "?a"),
-        
-
-        rewrite!("distribute"; "(* ?a (+ ?b ?c))"        => "(+ (* ?a ?b) (* ?a ?c))"),
-        rewrite!("factor"    ; "(+ (* ?a ?b) (* ?a ?c))" => "(* ?a (+ ?b ?c))"),
-
-        // These div rules are **not** unsound, even with the possibility of dividing by zero.
-        // Note how the left and right sides are "equally" sound, i.e., you always divide by ?c
-        // You get in trouble when you make things more or less "sound" by changing what you divide by
-        rewrite!("add-to-frac"; "(+ ?a (/ ?b ?c))" => "(/ (+ (* ?a ?c) ?b) ?c)"),
-        rewrite!("frac-to-add"; "(/ (+ (* ?a ?c) ?b) ?c)" => "(+ ?a (/ ?b ?c))" ),
-        rewrite!("mul-div";     "(* ?a (/ ?b ?c))" => "(/ (* ?a ?b) ?c)"),
-        rewrite!("div-mul";     "(/ (* ?a ?b) ?c)" => "(* ?a (/ ?b ?c))"),
-        rewrite!("frac-lift";   "(/ ?a ?b)" => "(/ (- ?b (- ?b ?a)) ?b)"),
-
-        // We can make these sound now by using the interval information!
-        // The `if` syntax allows you to add a `Condition` to a rewrite
-
-        rewrite!("cancel-div"; "(/ ?a ?a)" => "1" if is_non_zero("?a")),
-        rewrite!("zero-div"; "(/ 0 ?a)" => "0" if is_non_zero("?a")),
-    ]
-}
-
-// The function signature output here implements the `Condition` trait in egg.
-// So by making a function that outputs such functions, we have a "factory" for easily making
-// `Condition`s to be used in rules.
-fn is_non_zero(var: &str) -> impl Fn(&mut EGraph<Math, IntervalAnalysis>, Id, &Subst) -> bool {
-    let var: Var = var.parse().unwrap();
-    move |egraph, _root, subst: &Subst| {
-        let id = subst[var];
-        !egraph[id].data.contains_zero()
-    }
+    interval_rules()
 }
 
 egg::test_fn! { div_zero_doesnt_crash, rules(), "(* 1 (/ 0 0))" => "(/ 0 0)" }
@@ -220,12 +117,12 @@ fn optimize_expr_from_egraphs_paper() {
 // Let's now package up that functionality into a function,
 // and you can use this to write your own tests later.
 fn optimize_interval(s: &str, intervals: &[(&str, &str)]) -> Interval {
-    let expr: RecExpr<Math> = s.parse().unwrap();
-    let mut runner = Runner::default().with_expr(&expr);
+    let expr = parse_math(s).unwrap();
+    let mut runner = Runner::<Math, IntervalAnalysis, ()>::default().with_expr(&expr);
     let root = runner.roots[0];
 
     for (e, interval) in intervals {
-        let e: RecExpr<Math> = e.parse().unwrap();
+        let e = parse_math(e).unwrap();
         let id = runner.egraph.add_expr(&e);
         let interval: Interval = ival(interval);
         runner.egraph.set_analysis_data(id, interval)
@@ -234,12 +131,17 @@ fn optimize_interval(s: &str, intervals: &[(&str, &str)]) -> Interval {
     // we have to call rebuild to propagate the updated
     // analysis information throughout the e-graph
     runner.egraph.rebuild();
-    println!("Initial interval: {}", runner.egraph[root].data);
+    let initial_interval = runner.egraph[root].data.clone();
+    println!("Initial interval: {}", initial_interval);
 
     runner = runner.run(&rules());
 
+    // merging only ever intersects, so the range can't get any wider
     let final_interval = runner.egraph[root].data.clone();
+    let (before, after) = (initial_interval.spread(), final_interval.spread());
+    assert!(after <= before);
     println!("Final interval:   {}", final_interval);
+    println!("Width went from {} to {}", before, after);
     final_interval
 }
 
@@ -258,6 +160,15 @@ fn test_paper_example() {
     );
 }
 
+// The paper example again, written the way you'd write it on paper
+#[test]
+fn test_paper_example_infix() {
+    assert_eq!(
+        optimize_interval("1 - 2*y/(x + y)", &[("x", "0, 1"), ("y", "1, 2")]),
+        ival("-1, 0")
+    );
+}
+
 #[test]
 fn test_other_paper_example() {
     let intervals = &[("x", "1, 2"), ("y", "1, 2")];
@@ -277,3 +188,54 @@ fn squares() {
         ival("-4, 4"),
     )
 }
+
+#[test]
+fn half_bounded_variables() {
+    // "x is non-negative" survives through products and quotients
+    assert_eq!(
+        optimize_interval("(/ (* x y) y)", &[("x", "0, inf"), ("y", "1, 2")]),
+        ival("0, inf"),
+    )
+}
+
+#[test]
+fn strictly_positive_division() {
+    // With an open lower bound, x can't be zero, so x / x cancels
+    assert_eq!(
+        optimize_interval("(/ x x)", &[("x", "(0, 5]")]),
+        ival("[1, 1]")
+    );
+}
+
+#[test]
+#[should_panic(expected = "bad merge!")]
+fn contradictory_intervals() {
+    // (+ x 0) is provably equal to x, so telling the e-graph that
+    // they have disjoint ranges is an infeasible assumption.
+    optimize_interval("(+ x 0)", &[("x", "0, 1"), ("(+ x 0)", "2, 3")]);
+}
+
+#[test]
+fn subdivision_baseline() {
+    // Splitting up the input ranges is another way to tighten the result,
+    // so it makes a good sanity check for what the e-graph finds.
+    let expr: RecExpr<Math> = "(- 1 (/ (* 2 y) (+ x y)))".parse().unwrap();
+    let input = IntervalBox::new([("x", ival("0, 1")), ("y", ival("1, 2"))]);
+    // `interval_eval` is plain interval arithmetic, no e-graph involved
+    let eval =
+        |b: &IntervalBox| interval_eval(&expr, &b.iter().map(|(v, i)| (v, i.clone())).collect());
+
+    let naive = eval(&input);
+    let subdivided = subdivide(&input, 8, &eval);
+    let egraph = optimize_interval(&expr.to_string(), &[("x", "0, 1"), ("y", "1, 2")]);
+    assert_eq!(naive, ival("-3, 1/3"));
+    assert!(subdivided.spread() < naive.spread());
+
+    // both are sound, but the e-graph gets the exact range with no splitting at all
+    assert!(egraph.is_subset_of(&subdivided));
+    assert!(subdivided.is_subset_of(&naive));
+    println!(
+        "naive {}, subdivided {}, e-graph {}",
+        naive, subdivided, egraph
+    );
+}